@@ -0,0 +1,227 @@
+//! An interrupt-safe WaitGroup built on [`critical-section`].
+//!
+//! [`WaitGroup`](crate::cs::WaitGroup) keeps its counter in a
+//! `critical_section::Mutex<Cell<usize>>`, so it needs no atomics and no
+//! allocator: every `add`, `done` and check of the counter runs inside a
+//! critical section. On a single-core microcontroller this makes
+//! [`done`](crate::cs::WaitGroup::done) safe to call from an interrupt
+//! handler while the main loop waits.
+//!
+//! It is meant to live in a `static` —
+//! [`WaitGroup::new`](crate::cs::WaitGroup::new) is `const` — and is shared
+//! by reference rather than by cloning.
+//!
+//! While it waits, the main loop can put the core to sleep instead of
+//! spinning: [`wait_idle`](crate::cs::WaitGroup::wait_idle) runs its
+//! closure *inside* the critical section that just saw a non-zero counter.
+//! On Cortex-M, `WFI` wakes the core on a pending interrupt even while
+//! interrupts are masked, so passing `cortex_m::asm::wfi` there cannot miss
+//! a `done` that arrives between the check and the sleep; the interrupt is
+//! serviced as soon as the critical section ends.
+//!
+//! The final binary must provide a `critical-section` implementation, e.g.
+//! `cortex-m`'s `critical-section-single-core` feature, or
+//! `critical-section`'s `std` feature on hosted targets.
+//!
+//! Prefer [`spin::WaitGroupRef`](crate::spin::WaitGroupRef) when the
+//! target has atomic compare-and-swap and nothing waits in an interrupt
+//! context.
+//!
+//! [`critical-section`]: https://docs.rs/critical-section
+
+use core::cell::Cell;
+
+use critical_section::Mutex;
+
+use crate::{
+  primitives::spin_yield,
+  spin::{Relax, Spin},
+};
+
+/// A WaitGroup whose counter is guarded by a critical section.
+///
+/// See the [module documentation](self) for when to use it.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::cs::WaitGroup;
+///
+/// static WG: WaitGroup = WaitGroup::new();
+///
+/// // Main loop: arm the group, then start the peripherals whose
+/// // interrupt handlers call `WG.done()`.
+/// WG.add(2);
+/// # WG.done(); WG.done();
+///
+/// // On Cortex-M: `WG.wait_idle(cortex_m::asm::wfi)`.
+/// WG.wait_idle(|| {});
+/// assert_eq!(WG.remaining(), 0);
+/// ```
+pub struct WaitGroup {
+  counter: Mutex<Cell<usize>>,
+  // Number of callers blocked in one of the `wait` methods.
+  waiters: Mutex<Cell<usize>>,
+}
+
+impl Default for WaitGroup {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl From<usize> for WaitGroup {
+  fn from(count: usize) -> Self {
+    Self {
+      counter: Mutex::new(Cell::new(count)),
+      waiters: Mutex::new(Cell::new(0)),
+    }
+  }
+}
+
+impl core::fmt::Debug for WaitGroup {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("WaitGroup")
+      .field("counter", &self.remaining())
+      .finish()
+  }
+}
+
+impl WaitGroup {
+  /// Creates a new `WaitGroup` with a counter of zero.
+  ///
+  /// This is a `const fn`, so the group can be placed in a `static`.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      counter: Mutex::new(Cell::new(0)),
+      waiters: Mutex::new(Cell::new(0)),
+    }
+  }
+
+  /// Increments the counter by `num` and returns `self`, for chaining.
+  ///
+  /// Calls that bring the counter up from zero must happen before any
+  /// [`wait`](Self::wait) call, as with the other variants.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the counter would overflow.
+  pub fn add(&self, num: usize) -> &Self {
+    critical_section::with(|cs| {
+      let counter = self.counter.borrow(cs);
+      let prev = counter.get();
+      match prev.checked_add(num) {
+        Some(new) => counter.set(new),
+        None => panic!("WaitGroup counter overflow: prev={prev}, num={num}"),
+      }
+    });
+    self
+  }
+
+  /// Decrements the counter by one and returns the remaining count.
+  ///
+  /// Safe to call from an interrupt handler. If the counter is already
+  /// zero, this call is a no-op and returns `0`. No panic is raised.
+  pub fn done(&self) -> usize {
+    self.done_n(1)
+  }
+
+  /// Decrements the counter by `num` in one critical section and returns
+  /// the remaining count.
+  ///
+  /// Underflow saturates: if `num` is larger than the remaining count,
+  /// the counter becomes zero. No panic is raised.
+  pub fn done_n(&self, num: usize) -> usize {
+    critical_section::with(|cs| {
+      let counter = self.counter.borrow(cs);
+      let new = counter.get().saturating_sub(num);
+      counter.set(new);
+      new
+    })
+  }
+
+  /// Returns the current counter value — the number of tasks still
+  /// waiting to complete.
+  pub fn remaining(&self) -> usize {
+    critical_section::with(|cs| self.counter.borrow(cs).get())
+  }
+
+  /// Returns the number of callers currently blocked in
+  /// [`wait`](Self::wait), [`wait_with`](Self::wait_with) or
+  /// [`wait_idle`](Self::wait_idle). Like [`remaining`](Self::remaining),
+  /// it is a snapshot.
+  pub fn waiters(&self) -> usize {
+    critical_section::with(|cs| self.waiters.borrow(cs).get())
+  }
+
+  /// Blocks (spinning) until the counter reaches zero.
+  ///
+  /// Use [`wait_idle`](Self::wait_idle) to sleep the core instead.
+  pub fn wait(&self) {
+    self.wait_with(Spin::new())
+  }
+
+  /// Blocks until the counter reaches zero, calling `relax` between
+  /// checks of the counter. `relax` runs with the critical section
+  /// released, so interrupts are serviced while it runs.
+  pub fn wait_with<R: Relax>(&self, mut relax: R) {
+    let _waiting = Waiting::new(self);
+    while self.remaining() != 0 {
+      relax.relax();
+      spin_yield();
+    }
+  }
+
+  /// Blocks until the counter reaches zero, calling `idle` *inside* the
+  /// critical section each time the counter is seen to be non-zero.
+  ///
+  /// Pass an instruction that sleeps until an interrupt is pending, such
+  /// as `cortex_m::asm::wfi`. Because the check and the sleep happen in
+  /// the same critical section, an interrupt that calls
+  /// [`done`](Self::done) in between still wakes the core: it stays
+  /// pending until `idle` returns and the critical section ends.
+  ///
+  /// `idle` must return once an interrupt is pending; a closure that
+  /// waits for something else would hold the critical section, and with
+  /// it every interrupt, indefinitely.
+  pub fn wait_idle<F: FnMut()>(&self, mut idle: F) {
+    let _waiting = Waiting::new(self);
+    loop {
+      let finished = critical_section::with(|cs| {
+        if self.counter.borrow(cs).get() == 0 {
+          return true;
+        }
+        idle();
+        false
+      });
+      if finished {
+        return;
+      }
+      spin_yield();
+    }
+  }
+}
+
+/// One blocked caller, counted in [`WaitGroup::waiters`] until dropped,
+/// so a `relax` or `idle` that panics does not leave it counted.
+struct Waiting<'a>(&'a WaitGroup);
+
+impl<'a> Waiting<'a> {
+  fn new(wg: &'a WaitGroup) -> Self {
+    critical_section::with(|cs| {
+      let waiters = wg.waiters.borrow(cs);
+      waiters.set(waiters.get() + 1);
+    });
+    Self(wg)
+  }
+}
+
+impl Drop for Waiting<'_> {
+  fn drop(&mut self) {
+    critical_section::with(|cs| {
+      let waiters = self.0.waiters.borrow(cs);
+      waiters.set(waiters.get() - 1);
+    });
+  }
+}
@@ -1,23 +1,88 @@
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 use event_listener::{Event, EventListener};
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 use core::{
   pin::Pin,
-  sync::atomic::{AtomicUsize, Ordering},
   task::{Context, Poll},
 };
 
-#[cfg(feature = "triomphe")]
-use triomphe::Arc;
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+use crate::primitives::Weak;
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+use crate::{
+  primitives::{Arc, AtomicUsize, Ordering},
+  seal::Seal,
+  waiters::{Waiters, Waiting},
+  Sealed, WaitOutcome,
+};
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+use std::vec::Vec;
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+mod track;
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+pub use track::Tracked;
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "stream"))]
+pub use track::TrackedStream;
 
-#[cfg(all(any(feature = "std", feature = "alloc"), not(feature = "triomphe")))]
-use std::sync::Arc;
+#[cfg(all(feature = "std", feature = "future"))]
+mod phaser;
+#[cfg(all(feature = "std", feature = "future"))]
+pub use phaser::{AwaitPhase, Phaser};
 
+#[cfg(all(feature = "atomic-waker", not(loom)))]
+mod slots;
+#[cfg(all(feature = "atomic-waker", not(loom)))]
+pub use slots::{StaticWaitGroup, StaticWaitGroupFuture};
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 #[derive(Debug)]
 struct AsyncInner {
   counter: AtomicUsize,
   event: Event,
+  // Largest threshold a `wait_for_at_most` future is waiting for, stored
+  // as `n + 1` so that `0` means "none" and `n = 0` still works.
+  threshold: AtomicUsize,
+  // Threshold waiters listen here, so decrements that satisfy them don't
+  // wake the plain waiters on `event`.
+  thr_event: Event,
+  seal: Seal,
+  // Number of `reset` calls so far.
+  resets: AtomicUsize,
+  waiters: Waiters,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl AsyncInner {
+  fn new(count: usize) -> Self {
+    Self {
+      counter: AtomicUsize::new(count),
+      event: Event::new(),
+      threshold: AtomicUsize::new(0),
+      thr_event: Event::new(),
+      seal: Seal::new(),
+      resets: AtomicUsize::new(0),
+      waiters: Waiters::new(),
+    }
+  }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Drop for AsyncInner {
+  fn drop(&mut self) {
+    // A `WeakWaitFuture` listens on `event` without keeping the group
+    // alive; wake it so it sees the last handle is gone.
+    self.event.notify(usize::MAX);
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 /// A WaitGroup waits for a collection of tasks to finish.
 ///
 /// The main thread calls [`add`] to set the number of
@@ -56,43 +121,77 @@ struct AsyncInner {
 /// # })
 /// ```
 ///
+/// # Mixing threads and tasks
+///
+/// A single `WaitGroup` can be shared between OS threads and async tasks.
+/// Tasks await [`wait`], threads call [`wait_blocking`], and workers of
+/// either kind call [`done`]. Both kinds of waiter sleep on the same
+/// event — neither busy-polls — and are released by the same `done`:
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use wg::future::WaitGroup;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let wg = WaitGroup::new();
+///
+/// // A worker thread and a worker task.
+/// let t_wg = wg.add(2);
+/// std::thread::spawn({
+///     let t_wg = t_wg.clone();
+///     move || t_wg.done()
+/// });
+/// tokio::spawn(async move { t_wg.done() });
+///
+/// // A waiter thread and a waiter task.
+/// let blocking = std::thread::spawn({
+///     let wg = wg.clone();
+///     move || wg.wait_blocking()
+/// });
+/// wg.wait().await;
+/// blocking.join().unwrap();
+/// # })
+/// # }
+/// ```
+///
 /// [`wait`]: struct.WaitGroup.html#method.wait
+/// [`wait_blocking`]: struct.WaitGroup.html#method.wait_blocking
+/// [`done`]: struct.WaitGroup.html#method.done
 /// [`add`]: struct.WaitGroup.html#method.add
 #[cfg_attr(docsrs, doc(cfg(feature = "future")))]
 pub struct WaitGroup {
   inner: Arc<AsyncInner>,
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl WaitGroup {
+  fn from_inner(inner: Arc<AsyncInner>) -> Self {
+    Self { inner }
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 impl Default for WaitGroup {
   fn default() -> Self {
-    Self {
-      inner: Arc::new(AsyncInner {
-        counter: AtomicUsize::new(0),
-        event: Event::new(),
-      }),
-    }
+    Self::from_inner(Arc::new(AsyncInner::new(0)))
   }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 impl From<usize> for WaitGroup {
   fn from(count: usize) -> Self {
-    Self {
-      inner: Arc::new(AsyncInner {
-        counter: AtomicUsize::new(count),
-        event: Event::new(),
-      }),
-    }
+    Self::from_inner(Arc::new(AsyncInner::new(count)))
   }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 impl Clone for WaitGroup {
   fn clone(&self) -> Self {
-    Self {
-      inner: self.inner.clone(),
-    }
+    Self::from_inner(self.inner.clone())
   }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 impl core::fmt::Debug for WaitGroup {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     f.debug_struct("WaitGroup")
@@ -101,7 +200,10 @@ impl core::fmt::Debug for WaitGroup {
   }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 /// Shorthand for [`add`](WaitGroup::add), discarding the returned clone.
+/// Like `add`, it panics if the group has been
+/// [sealed](WaitGroup::seal).
 ///
 /// ```
 /// use wg::future::WaitGroup;
@@ -115,6 +217,23 @@ impl core::ops::AddAssign<usize> for WaitGroup {
   }
 }
 
+/// Shorthand for [`done_n`](WaitGroup::done_n), discarding the remaining
+/// count.
+///
+/// ```
+/// use wg::future::WaitGroup;
+/// let mut wg = WaitGroup::from(3);
+/// wg -= 2;
+/// assert_eq!(wg.remaining(), 1);
+/// ```
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::ops::SubAssign<usize> for WaitGroup {
+  fn sub_assign(&mut self, rhs: usize) {
+    self.done_n(rhs);
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 impl WaitGroup {
   /// Creates a new `WaitGroup`
   pub fn new() -> Self {
@@ -170,12 +289,27 @@ impl WaitGroup {
   ///
   /// Calls that bring the counter up from zero must happen before any
   /// [`wait`](Self::wait) call — typically by running them on the main
-  /// task before spawning the workers.
+  /// task before spawning the workers. Work that discovers more work
+  /// while a task waits should add it with [`Token::fork`] instead.
   ///
   /// If a `WaitGroup` is reused for several independent rounds, new
   /// `add` calls must happen after all previous [`wait`](Self::wait)
   /// calls have returned.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
   pub fn add(&self, num: usize) -> Self {
+    match self.try_add(num) {
+      Ok(wg) => wg,
+      Err(_) => panic!("add on a sealed WaitGroup"),
+    }
+  }
+
+  /// Like [`add`](Self::add), but returns `Err(Sealed)` instead of
+  /// adding once the group has been [sealed](Self::seal).
+  pub fn try_add(&self, num: usize) -> Result<Self, Sealed> {
+    self.inner.seal.precheck()?;
     // Use `fetch_update` + `checked_add` so overflow is caught in ALL
     // builds, not just debug. A plain `fetch_add` would silently wrap
     // in release mode, which could reset the counter to zero and let
@@ -187,10 +321,12 @@ impl WaitGroup {
         prev.checked_add(num)
       })
       .expect("WaitGroup counter overflow");
-
-    Self {
-      inner: self.inner.clone(),
+    if let Err(sealed) = self.inner.seal.check() {
+      self.done_n(num);
+      return Err(sealed);
     }
+
+    Ok(self.clone())
   }
 
   /// Decrements the `WaitGroup` counter by one and returns the
@@ -215,24 +351,65 @@ impl WaitGroup {
   /// # })
   /// ```
   pub fn done(&self) -> usize {
-    match self
-      .inner
-      .counter
-      .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| v.checked_sub(1))
-    {
-      Ok(old) => {
-        let remaining = old - 1;
-        // Only notify when the counter actually reaches zero. Waking
-        // listeners on every decrement just makes them re-check and
-        // sleep again, wasting work.
-        if remaining == 0 {
-          self.inner.event.notify(usize::MAX);
-        }
-        remaining
-      }
-      // Over-done: counter was already zero. Silently no-op.
-      Err(_) => 0,
+    self.done_n(1)
+  }
+
+  /// Decrements the counter by `num` and returns the remaining count —
+  /// the equivalent of Go's `Add(-n)`.
+  ///
+  /// This is the same as calling [`done`](Self::done) `num` times, but
+  /// it is a single atomic update and notifies waiters at most once,
+  /// when the counter reaches zero.
+  ///
+  /// Underflow saturates: if `num` is larger than the remaining count,
+  /// the counter becomes zero, just as `done` on a zero counter is a
+  /// no-op. No panic is raised.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::future::WaitGroup;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(100);
+  /// tokio::spawn(async move {
+  ///     // process 100 items, then report them all at once
+  ///     t_wg.done_n(100);
+  /// });
+  /// wg.wait().await;
+  /// # })
+  /// ```
+  pub fn done_n(&self, num: usize) -> usize {
+    // Saturate at zero; an already-zero counter is left untouched.
+    let remaining =
+      match self
+        .inner
+        .counter
+        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+          (v != 0).then(|| v.saturating_sub(num))
+        }) {
+        Ok(old) => old.saturating_sub(num),
+        // Over-done: counter was already zero. Silently no-op.
+        Err(_) => return 0,
+      };
+
+    // Only notify when the counter actually reaches zero. Waking
+    // listeners on every decrement just makes them re-check and
+    // sleep again, wasting work.
+    if remaining == 0 {
+      self.inner.event.notify(usize::MAX);
+    }
+
+    // Threshold waiters are notified once the largest registered
+    // threshold is reached; those still above their own threshold
+    // register it again. See `WaitForAtMostFuture::poll` for why this
+    // load cannot miss a waiter that missed our decrement.
+    let thr = self.inner.threshold.load(Ordering::Acquire);
+    if thr != 0 && remaining < thr && self.inner.threshold.swap(0, Ordering::AcqRel) != 0 {
+      self.inner.thr_event.notify(usize::MAX);
     }
+    remaining
   }
 
   /// Returns the current counter value — the number of tasks still
@@ -241,6 +418,52 @@ impl WaitGroup {
     self.inner.counter.load(Ordering::Acquire)
   }
 
+  /// Returns the number of waiters currently registered on this group,
+  /// through any of its handles: pending futures from
+  /// [`wait`](Self::wait), [`wait_for_at_most`](Self::wait_for_at_most),
+  /// [`wait_outcome`](Self::wait_outcome) and
+  /// `WeakWaitGroup::wait`, pending [`wait_all`] and [`wait_any`]
+  /// futures with this group among theirs, handles whose last
+  /// [`PollWait`] states whose last [`poll_wait`](Self::poll_wait) returned
+  /// `Pending`, and threads
+  /// blocked in `wait_blocking` or `wait_blocking_timeout`.
+  /// A `wait_all` future counts on the one group it is waiting for, a
+  /// `wait_any` future on every group.
+  ///
+  /// A future stops counting once it completes or is dropped, so a
+  /// cancelled wait — e.g. one that lost a `select!` — does not linger.
+  /// The value is a snapshot, for diagnostics or to skip work that only
+  /// matters to a waiter.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use std::time::Duration;
+  /// use wg::future::WaitGroup;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  ///
+  /// // A wait that timed out was dropped, and no longer counts.
+  /// let _ = tokio::time::timeout(Duration::from_millis(10), wg.wait()).await;
+  /// assert_eq!(wg.waiters(), 0);
+  ///
+  /// let waiter = tokio::spawn({
+  ///     let wg = wg.clone();
+  ///     async move { wg.wait().await }
+  /// });
+  /// while wg.waiters() == 0 {
+  ///     tokio::task::yield_now().await;
+  /// }
+  /// t_wg.done();
+  /// waiter.await.unwrap();
+  /// # })
+  /// ```
+  pub fn waiters(&self) -> usize {
+    self.inner.waiters.count()
+  }
+
   /// wait blocks until the [`WaitGroup`] counter is zero.
   ///
   /// # Example
@@ -266,35 +489,214 @@ impl WaitGroup {
     WaitGroupFuture {
       inner: self,
       notified: self.inner.event.listen(),
+      waiting: None,
       _pin: core::marker::PhantomPinned,
     }
   }
 
-  /// Wait blocks until the [`WaitGroup`] counter is zero. This method is
-  /// intended to be used in a non-async context,
-  /// e.g. when implementing the [`Drop`] trait.
+  /// Seals the group: from now on, [`try_add`](Self::try_add) fails and
+  /// [`add`](Self::add) panics, while the work already counted runs to
+  /// completion. See [`crate::WaitGroup::seal`].
+  pub fn seal(&self) {
+    self.inner.seal.seal();
+  }
+
+  /// Returns `true` once the group has been [sealed](Self::seal).
+  pub fn is_sealed(&self) -> bool {
+    self.inner.seal.is_sealed()
+  }
+
+  /// Forcibly sets the counter to zero and wakes every waiting task.
+  /// Those awaiting [`wait_outcome`](Self::wait_outcome) get
+  /// [`WaitOutcome::Reset`]. See [`crate::WaitGroup::reset`].
+  pub fn reset(&self) {
+    // Published by the `SeqCst` store below.
+    self.inner.resets.fetch_add(1, Ordering::Relaxed);
+    self.inner.counter.store(0, Ordering::SeqCst);
+    self.inner.event.notify(usize::MAX);
+    self.inner.threshold.store(0, Ordering::Release);
+    self.inner.thr_event.notify(usize::MAX);
+  }
+
+  /// Like [`wait`](Self::wait), but the future also reports whether the
+  /// counter drained or the group was [reset](Self::reset) in the
+  /// meantime.
   ///
-  /// The implementation is like a spin lock, which is not efficient, so use it with caution.
+  /// The outcome is relative to when the future was created, not first
+  /// polled.
   ///
   /// # Example
   ///
   /// ```rust
-  /// use wg::future::WaitGroup;
+  /// use wg::{future::WaitGroup, WaitOutcome};
   ///
   /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
   /// let wg = WaitGroup::new();
   /// wg.add(1);
   /// let t_wg = wg.clone();
-  ///
   /// tokio::spawn(async move {
-  ///     // do some time consuming task
-  ///     t_wg.done()
+  ///     // the worker is stuck; give up on it
+  ///     tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+  ///     t_wg.reset();
   /// });
+  /// assert_eq!(wg.wait_outcome().await, WaitOutcome::Reset);
+  /// # })
+  /// ```
+  pub fn wait_outcome(&self) -> WaitOutcomeFuture<'_> {
+    WaitOutcomeFuture {
+      inner: &self.inner,
+      resets: self.inner.resets.load(Ordering::Acquire),
+      listener: None,
+      waiting: None,
+    }
+  }
+
+  /// Polls for the counter to reach zero, for hand-written [`Future`] and
+  /// `Stream` implementations.
   ///
-  /// // wait other thread completes
-  /// wg.wait_blocking();
+  /// Returns [`Poll::Ready`] once the counter is zero. Otherwise it
+  /// registers `cx`'s waker in `wait` and returns [`Poll::Pending`]; the
+  /// task is woken by the `done` that brings the counter to zero. Unlike
+  /// [`wait`](Self::wait) there is no future to store: [`PollWait`] has no
+  /// lifetime and is `Unpin`, so a manual implementation needs neither
+  /// pinning nor a borrow of the `WaitGroup`.
+  ///
+  /// The registration lives in `wait`, which the caller owns, so the
+  /// handle itself is only borrowed and can be shared. Keep one
+  /// `PollWait` per polling task.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use std::{
+  ///     future::Future,
+  ///     pin::Pin,
+  ///     task::{Context, Poll},
+  /// };
+  /// use wg::future::{PollWait, WaitGroup};
+  ///
+  /// /// Resolves to `value` once every worker is done.
+  /// struct AfterWorkers<T> {
+  ///     wg: WaitGroup,
+  ///     wait: PollWait,
+  ///     value: Option<T>,
+  /// }
+  ///
+  /// impl<T: Unpin> Future for AfterWorkers<T> {
+  ///     type Output = T;
+  ///
+  ///     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+  ///         let this = self.get_mut();
+  ///         std::task::ready!(this.wg.poll_wait(&mut this.wait, cx));
+  ///         Poll::Ready(this.value.take().expect("polled after completion"))
+  ///     }
+  /// }
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  /// tokio::spawn(async move { t_wg.done() });
+  ///
+  /// let after = AfterWorkers {
+  ///     wg,
+  ///     wait: PollWait::new(),
+  ///     value: Some(42),
+  /// };
+  /// assert_eq!(after.await, 42);
   /// # })
   /// ```
+  ///
+  /// [`Future`]: core::future::Future
+  pub fn poll_wait(&self, wait: &mut PollWait, cx: &mut Context<'_>) -> Poll<()> {
+    if wait
+      .counted
+      .as_ref()
+      .is_some_and(|inner| !Arc::ptr_eq(inner, &self.inner))
+    {
+      // Last polled against another group: drop that registration.
+      *wait = PollWait::new();
+    }
+    let poll = poll_drained(&self.inner, &mut wait.listener, cx);
+    if poll.is_ready() {
+      wait.untrack();
+    } else if wait.counted.is_none() {
+      self.inner.waiters.arrive();
+      wait.counted = Some(self.inner.clone());
+    }
+    poll
+  }
+
+  /// Returns a future that resolves once at most `n` tasks remain, i.e.
+  /// once the counter is `<= n`. `wait_for_at_most(0)` behaves like
+  /// [`wait`](Self::wait).
+  ///
+  /// Useful for quorums and pipelines that can continue once enough
+  /// workers have finished. Threshold waiters are notified separately
+  /// from plain waiters, and only by the `done` that brings the counter
+  /// down to the largest threshold currently waited for — not on every
+  /// decrement.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::future::WaitGroup;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// for _ in 0..5 {
+  ///     let t_wg = wg.add(1);
+  ///     tokio::spawn(async move {
+  ///         // query one replica
+  ///         t_wg.done();
+  ///     });
+  /// }
+  ///
+  /// // Quorum: continue once 3 of the 5 replicas have answered.
+  /// wg.wait_for_at_most(2).await;
+  /// assert!(wg.remaining() <= 2);
+  /// # })
+  /// ```
+  pub fn wait_for_at_most(&self, n: usize) -> WaitForAtMostFuture<'_> {
+    WaitForAtMostFuture {
+      inner: self,
+      n,
+      listener: None,
+      waiting: None,
+    }
+  }
+
+  /// Wait blocks until the [`WaitGroup`] counter is zero. This method is
+  /// intended to be used in a non-async context, e.g. from a plain thread
+  /// or when implementing the [`Drop`] trait.
+  ///
+  /// The calling thread is parked on the same event that
+  /// [`wait`](Self::wait) futures listen on — it does not spin or poll —
+  /// so one [`done`](Self::done) bringing the counter to zero wakes
+  /// blocking and async waiters alike. See
+  /// [Mixing threads and tasks](WaitGroup#mixing-threads-and-tasks).
+  ///
+  /// Do not call it from inside an async task: it blocks the executor
+  /// thread until the counter drains.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::future::WaitGroup;
+  ///
+  /// let rt = tokio::runtime::Runtime::new().unwrap();
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  ///
+  /// rt.spawn(async move {
+  ///     // do some time consuming task
+  ///     t_wg.done();
+  /// });
+  ///
+  /// // Block a plain thread, outside the runtime, until the task is done.
+  /// std::thread::spawn(move || wg.wait_blocking())
+  ///     .join()
+  ///     .unwrap();
+  /// ```
   // `EventListener::wait()` (blocking) is not available on
   // `target_family = "wasm"` — the platform has no OS threads. Gate
   // this method out so `cargo check --all-features --target
@@ -304,6 +706,7 @@ impl WaitGroup {
   pub fn wait_blocking(&self) {
     use event_listener::Listener;
 
+    let _waiting = self.inner.waiters.enter();
     while self.inner.counter.load(Ordering::Acquire) != 0 {
       let ln = self.inner.event.listen();
       // Re-check after creating the listener to close the lost-wakeup
@@ -315,8 +718,552 @@ impl WaitGroup {
       ln.wait();
     }
   }
+
+  /// Like [`wait_blocking`](Self::wait_blocking), but gives up after
+  /// `timeout`. Returns `true` if the counter reached zero, `false` if the
+  /// timeout elapsed first.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use std::time::Duration;
+  /// use wg::future::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let _stuck = wg.add(1);
+  /// assert!(!wg.wait_blocking_timeout(Duration::from_millis(10)));
+  ///
+  /// wg.done();
+  /// assert!(wg.wait_blocking_timeout(Duration::from_millis(10)));
+  /// ```
+  // `Listener::wait_deadline` has the same platform limits as
+  // `Listener::wait`; loom does not model timeouts.
+  #[cfg(all(feature = "std", not(target_family = "wasm"), not(loom)))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "std", not(target_family = "wasm")))))]
+  pub fn wait_blocking_timeout(&self, timeout: std::time::Duration) -> bool {
+    use event_listener::Listener;
+
+    let _waiting = self.inner.waiters.enter();
+    // A deadline too far out to represent is as good as none.
+    let deadline = std::time::Instant::now().checked_add(timeout);
+    while self.inner.counter.load(Ordering::Acquire) != 0 {
+      let ln = self.inner.event.listen();
+      // Re-check after creating the listener, as in `wait_blocking`.
+      if self.inner.counter.load(Ordering::Acquire) == 0 {
+        return true;
+      }
+      match deadline {
+        Some(deadline) => {
+          if ln.wait_deadline(deadline).is_none() {
+            return self.inner.counter.load(Ordering::Acquire) == 0;
+          }
+        }
+        None => ln.wait(),
+      }
+    }
+    true
+  }
+
+  /// Adds one unit to the counter and returns a [`Token`] for it.
+  ///
+  /// Unlike the handle returned by [`add`](Self::add), a token can only
+  /// complete its own units, once: [`Token::done`] consumes it. It cannot
+  /// wait, add, or complete anybody else's work.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::future::WaitGroup;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// let token = wg.add_token();
+  /// tokio::spawn(async move { token.done() });
+  /// wg.wait().await;
+  /// # })
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn add_token(&self) -> Token {
+    self.add_tokens(1)
+  }
+
+  /// Adds `num` units to the counter and returns a single [`Token`]
+  /// holding all of them. Use [`Token::split`] to hand parts of it to
+  /// different workers.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn add_tokens(&self, num: usize) -> Token {
+    Token {
+      wg: self.add(num),
+      units: num,
+    }
+  }
+
+  /// Wraps `fut` so that it counts as one unit on this group until it
+  /// completes.
+  ///
+  /// The counter is incremented here, and decremented when the returned
+  /// future produces its output or is dropped before that, so futures
+  /// cancelled by `select!`, dropped from a `FuturesUnordered` or
+  /// abandoned by a spawner are counted as finished as well. The wrapped
+  /// future itself needs no changes.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::future::WaitGroup;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// for i in 0..3 {
+  ///     tokio::spawn(wg.track(async move {
+  ///         // an unmodified task body
+  ///         i * 2
+  ///     }));
+  /// }
+  ///
+  /// wg.wait().await;
+  /// # })
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn track<F>(&self, fut: F) -> Tracked<F>
+  where
+    F: core::future::Future,
+  {
+    Tracked::new(fut, self.add_token())
+  }
+
+  /// Wraps `stream` so that it counts as one unit on this group until it
+  /// is exhausted, i.e. until it yields `None`, or the returned stream is
+  /// dropped.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  #[cfg(feature = "stream")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+  pub fn track_stream<S>(&self, stream: S) -> TrackedStream<S>
+  where
+    S: futures_core::Stream,
+  {
+    TrackedStream::new(stream, self.add_token())
+  }
+
+  /// Creates a [`WeakWaitGroup`] observing this group without keeping it
+  /// alive.
+  ///
+  /// Only available when the selected `Arc` keeps a weak count, i.e. not
+  /// with the `triomphe` feature (unless `portable-atomic` overrides it).
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::future::WaitGroup;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  /// let observer = wg.downgrade();
+  /// assert_eq!(observer.remaining(), 1);
+  ///
+  /// drop(wg);
+  /// let worker = tokio::spawn(async move { t_wg.done() });
+  /// observer.wait().await;
+  /// assert_eq!(observer.remaining(), 0);
+  ///
+  /// // Once the worker's handle is gone too, the group is freed.
+  /// worker.await.unwrap();
+  /// assert!(observer.upgrade().is_none());
+  /// # })
+  /// ```
+  #[cfg(all(
+    any(feature = "std", feature = "alloc"),
+    feature = "future",
+    not(loom),
+    any(feature = "portable-atomic", not(feature = "triomphe"))
+  ))]
+  #[cfg_attr(docsrs, doc(cfg(not(feature = "triomphe"))))]
+  pub fn downgrade(&self) -> WeakWaitGroup {
+    WeakWaitGroup {
+      inner: Arc::downgrade(&self.inner),
+    }
+  }
+}
+
+/// A weak handle to a [`WaitGroup`], created by
+/// [`WaitGroup::downgrade`].
+///
+/// It can read and wait on the counter, but does not keep the group
+/// alive: once every `WaitGroup` handle is dropped, the shared state is
+/// freed, [`upgrade`](Self::upgrade) returns `None` and [`wait`](Self::wait)
+/// resolves even if the counter never reached zero, since nothing is left
+/// to call `done`. See `wg::WeakWaitGroup` for details.
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+#[cfg_attr(docsrs, doc(cfg(not(feature = "triomphe"))))]
+pub struct WeakWaitGroup {
+  inner: Weak<AsyncInner>,
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl Clone for WeakWaitGroup {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl core::fmt::Debug for WeakWaitGroup {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("WeakWaitGroup")
+      .field("counter", &self.remaining())
+      .field("orphaned", &(self.inner.strong_count() == 0))
+      .finish()
+  }
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl WeakWaitGroup {
+  /// Returns a [`WaitGroup`] handle, or `None` if every handle has
+  /// already been dropped.
+  pub fn upgrade(&self) -> Option<WaitGroup> {
+    self.inner.upgrade().map(WaitGroup::from_inner)
+  }
+
+  /// Returns the current counter value, or `0` once every [`WaitGroup`]
+  /// handle has been dropped and the group is gone.
+  pub fn remaining(&self) -> usize {
+    self
+      .inner
+      .upgrade()
+      .map_or(0, |inner| inner.counter.load(Ordering::Acquire))
+  }
+
+  /// Returns a future that resolves once the counter reaches zero, or
+  /// once every [`WaitGroup`] handle has been dropped.
+  pub fn wait(&self) -> WeakWaitFuture<'_> {
+    WeakWaitFuture {
+      inner: &self.inner,
+      listener: None,
+      counted: false,
+    }
+  }
+}
+
+/// A future returned by [`WeakWaitGroup::wait()`].
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WeakWaitFuture<'a> {
+  inner: &'a Weak<AsyncInner>,
+  listener: Option<EventListener>,
+  // Whether this future is counted in the group's `waiters`.
+  counted: bool,
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl core::future::Future for WeakWaitFuture<'_> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    // Hold the group only for this poll: the listener alone does not keep
+    // it alive, and the drop of its last handle notifies it.
+    let Some(inner) = this.inner.upgrade() else {
+      this.listener = None;
+      return Poll::Ready(());
+    };
+    let poll = poll_drained(&inner, &mut this.listener, cx);
+    inner.waiters.track_owned(&mut this.counted, poll)
+  }
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  feature = "future",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl Drop for WeakWaitFuture<'_> {
+  fn drop(&mut self) {
+    if let Some(inner) = self.inner.upgrade() {
+      inner.waiters.untrack(&mut self.counted);
+    }
+  }
+}
+
+/// The caller-owned registration of [`WaitGroup::poll_wait`].
+///
+/// It holds the listener that wakes the polling task, so the `WaitGroup`
+/// handle only needs to be borrowed. It has no lifetime and is `Unpin`:
+/// store it next to the handle in a hand-written future or stream. While
+/// it is registered, it counts in [`WaitGroup::waiters`] and keeps the
+/// group alive.
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[derive(Debug, Default)]
+pub struct PollWait {
+  listener: Option<EventListener>,
+  // The group this registration is counted in, while it is pending.
+  counted: Option<Arc<AsyncInner>>,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl PollWait {
+  /// Creates an unregistered state; the first pending
+  /// [`poll_wait`](WaitGroup::poll_wait) registers it.
+  pub const fn new() -> Self {
+    Self {
+      listener: None,
+      counted: None,
+    }
+  }
+
+  fn untrack(&mut self) {
+    if let Some(inner) = self.counted.take() {
+      inner.waiters.leave();
+    }
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Drop for PollWait {
+  fn drop(&mut self) {
+    self.untrack();
+  }
+}
+
+/// A completion token for units of work added with
+/// [`WaitGroup::add_token`] or [`WaitGroup::add_tokens`].
+///
+/// [`done`](Self::done) consumes the token, it cannot wait on the group,
+/// and it only adds to it through [`fork`](Self::fork). Dropping it also
+/// completes its units. See [`crate::Token`] for details.
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[must_use = "dropping a `Token` completes its units right away"]
+pub struct Token {
+  wg: WaitGroup,
+  units: usize,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Drop for Token {
+  fn drop(&mut self) {
+    if self.units != 0 {
+      self.wg.done_n(self.units);
+    }
+  }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::fmt::Debug for Token {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Token").field("units", &self.units).finish()
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Token {
+  /// Returns the number of units this token completes.
+  pub fn units(&self) -> usize {
+    self.units
+  }
+
+  /// Completes every unit of this token at once and returns the
+  /// remaining count, like [`WaitGroup::done_n`].
+  pub fn done(mut self) -> usize {
+    let units = core::mem::take(&mut self.units);
+    self.wg.done_n(units)
+  }
+
+  /// Splits the token into one holding the first `at` units and one
+  /// holding the rest.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `at` is greater than [`units`](Self::units).
+  pub fn split(mut self, at: usize) -> (Token, Token) {
+    assert!(
+      at <= self.units,
+      "split point {at} out of range for a token of {} units",
+      self.units
+    );
+    let rest = Token {
+      wg: self.wg.clone(),
+      units: self.units - at,
+    };
+    self.units = at;
+    (self, rest)
+  }
+
+  /// Adds `num` units to the group for work discovered while this
+  /// token's own units are outstanding, and returns a token holding
+  /// them. Unlike [`WaitGroup::add`], this is safe while another task is
+  /// in `wait`; see [`crate::Token::fork`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if this token holds no units, since nothing would then keep
+  /// the counter from reaching zero before the new units are added, or if
+  /// the group has been [sealed](WaitGroup::seal).
+  pub fn fork(&self, num: usize) -> Token {
+    assert!(self.units != 0, "cannot fork a token that holds no units");
+    self.wg.add_tokens(num)
+  }
+}
+
+/// An async WaitGroup where every handle is one participant, and dropping
+/// a handle counts as `done`.
+///
+/// Cloning adds a participant and dropping a clone removes it;
+/// [`wait`](Self::wait) consumes the caller's own handle and resolves
+/// once every other clone has been dropped. See [`crate::AutoWaitGroup`]
+/// for the blocking variant.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::future::AutoWaitGroup;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let wg = AutoWaitGroup::new();
+/// for _ in 0..5 {
+///     let wg = wg.clone();
+///     tokio::spawn(async move {
+///         // do some work; dropping `wg` reports it finished
+///         drop(wg);
+///     });
+/// }
+///
+/// wg.wait().await;
+/// # })
+/// ```
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+pub struct AutoWaitGroup {
+  wg: WaitGroup,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Default for AutoWaitGroup {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Clone for AutoWaitGroup {
+  fn clone(&self) -> Self {
+    Self { wg: self.wg.add(1) }
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl Drop for AutoWaitGroup {
+  fn drop(&mut self) {
+    self.wg.done();
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::fmt::Debug for AutoWaitGroup {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("AutoWaitGroup")
+      .field("participants", &self.wg.remaining())
+      .finish()
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl AutoWaitGroup {
+  /// Creates a new group with one participant: the returned handle.
+  pub fn new() -> Self {
+    Self {
+      wg: WaitGroup::from(1),
+    }
+  }
+
+  /// Returns the number of live handles, including this one.
+  pub fn participants(&self) -> usize {
+    self.wg.remaining()
+  }
+
+  /// Returns the number of handles waiting on a pending
+  /// [`wait`](Self::wait) future, which no longer count as participants.
+  pub fn waiters(&self) -> usize {
+    self.wg.waiters()
+  }
+
+  /// Drops this handle and returns a future that resolves once every
+  /// other clone has been dropped too.
+  pub fn wait(self) -> AutoWaitGroupFuture {
+    // A plain clone shares the counter without adding a participant.
+    let wg = self.wg.clone();
+    drop(self);
+    AutoWaitGroupFuture {
+      wg,
+      wait: PollWait::new(),
+    }
+  }
+}
+
+/// A future returned by [`AutoWaitGroup::wait()`].
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AutoWaitGroupFuture {
+  wg: WaitGroup,
+  wait: PollWait,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::future::Future for AutoWaitGroupFuture {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    this.wg.poll_wait(&mut this.wait, cx)
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 pin_project_lite::pin_project! {
     /// A future returned by [`WaitGroup::wait()`].
     #[derive(Debug)]
@@ -325,15 +1272,28 @@ pin_project_lite::pin_project! {
         inner: &'a WaitGroup,
         #[pin]
         notified: EventListener,
+        waiting: Option<Waiting<'a>>,
         #[pin]
         _pin: core::marker::PhantomPinned,
     }
 }
 
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
 impl core::future::Future for WaitGroupFuture<'_> {
   type Output = ();
 
-  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let wg = self.inner;
+    let poll = self.as_mut().poll_counter(cx);
+    wg.inner.waiters.track(self.project().waiting, poll)
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl WaitGroupFuture<'_> {
+  fn poll_counter(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+    use core::future::Future;
+
     if self.inner.inner.counter.load(Ordering::Acquire) == 0 {
       return Poll::Ready(());
     }
@@ -387,3 +1347,268 @@ impl core::future::Future for WaitGroupFuture<'_> {
     }
   }
 }
+
+/// A future returned by [`WaitGroup::wait_outcome()`].
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitOutcomeFuture<'a> {
+  inner: &'a AsyncInner,
+  resets: usize,
+  listener: Option<EventListener>,
+  waiting: Option<Waiting<'a>>,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::fmt::Debug for WaitOutcomeFuture<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("WaitOutcomeFuture")
+      .field("resets", &self.resets)
+      .finish()
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::future::Future for WaitOutcomeFuture<'_> {
+  type Output = WaitOutcome;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    let poll = poll_drained(this.inner, &mut this.listener, cx);
+    match this.inner.waiters.track(&mut this.waiting, poll) {
+      Poll::Pending => Poll::Pending,
+      Poll::Ready(()) if this.inner.resets.load(Ordering::Acquire) == this.resets => {
+        Poll::Ready(WaitOutcome::Drained)
+      }
+      Poll::Ready(()) => Poll::Ready(WaitOutcome::Reset),
+    }
+  }
+}
+
+/// A future returned by [`WaitGroup::wait_for_at_most()`].
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitForAtMostFuture<'a> {
+  inner: &'a WaitGroup,
+  n: usize,
+  listener: Option<EventListener>,
+  waiting: Option<Waiting<'a>>,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::future::Future for WaitForAtMostFuture<'_> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    let poll = this.poll_counter(cx);
+    this.inner.inner.waiters.track(&mut this.waiting, poll)
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl WaitForAtMostFuture<'_> {
+  fn poll_counter(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+    use core::future::Future;
+
+    let inner = &self.inner.inner;
+    if inner.counter.load(Ordering::Acquire) <= self.n {
+      return Poll::Ready(());
+    }
+
+    loop {
+      // The counter is read with a read-modify-write: it either comes
+      // after a racing `done`'s decrement in the counter's modification
+      // order, and sees it, or before it, in which case that `done`
+      // synchronizes with us and its threshold load sees our
+      // registration below.
+      if inner.counter.fetch_add(0, Ordering::AcqRel) <= self.n {
+        return Poll::Ready(());
+      }
+
+      match self.listener.as_mut() {
+        // Listen first, then publish the threshold and re-check the
+        // counter at the top of the loop.
+        None => {
+          self.listener = Some(inner.thr_event.listen());
+          inner
+            .threshold
+            .fetch_max(self.n.saturating_add(1), Ordering::AcqRel);
+        }
+        Some(listener) => match Pin::new(listener).poll(cx) {
+          Poll::Pending => return Poll::Pending,
+          // Notified: the largest threshold was reached, which may not
+          // be ours. Re-check, and register again if needed.
+          Poll::Ready(()) => self.listener = None,
+        },
+      }
+    }
+  }
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+/// Polls one group towards zero, keeping its listener in `listener`.
+fn poll_drained(
+  inner: &AsyncInner,
+  listener: &mut Option<EventListener>,
+  cx: &mut Context<'_>,
+) -> Poll<()> {
+  loop {
+    if inner.counter.load(Ordering::Acquire) == 0 {
+      *listener = None;
+      return Poll::Ready(());
+    }
+
+    match listener.as_mut() {
+      // Listen, then re-check the counter at the top of the loop to
+      // close the lost-wakeup window.
+      None => *listener = Some(inner.event.listen()),
+      Some(l) => match core::future::Future::poll(Pin::new(l), cx) {
+        Poll::Pending => return Poll::Pending,
+        // Notified, but the counter may have gone up again since.
+        Poll::Ready(()) => *listener = None,
+      },
+    }
+  }
+}
+
+/// Returns a future that resolves once every group in `groups` has
+/// drained to zero.
+///
+/// The groups are awaited one after another, so each of them has been
+/// observed at zero at some point before the future resolves — the same
+/// guarantee as awaiting [`WaitGroup::wait`] on each in turn.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::future::WaitGroup;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let net = WaitGroup::new();
+/// let disk = WaitGroup::new();
+/// for wg in [&net, &disk] {
+///     let t_wg = wg.add(1);
+///     tokio::spawn(async move { t_wg.done() });
+/// }
+///
+/// wg::future::wait_all([&net, &disk]).await;
+/// # })
+/// ```
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+pub fn wait_all<'a, I>(groups: I) -> WaitAll<'a>
+where
+  I: IntoIterator<Item = &'a WaitGroup>,
+{
+  WaitAll {
+    groups: groups.into_iter().collect(),
+    next: 0,
+    listener: None,
+    waiting: None,
+  }
+}
+
+/// Returns a future that resolves once any group in `groups` drains to
+/// zero, with its index in `groups`, or with `None` if `groups` is empty.
+///
+/// If several groups are drained, the lowest index wins. The future
+/// listens on every group at once; it is woken by the `done` that brings
+/// one of them to zero and never polls `remaining`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use wg::future::WaitGroup;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let slow = WaitGroup::new();
+/// let fast = WaitGroup::new();
+/// let t_slow = slow.add(1);
+/// let t_fast = fast.add(1);
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(50)).await;
+///     t_slow.done();
+/// });
+/// tokio::spawn(async move { t_fast.done() });
+///
+/// assert_eq!(wg::future::wait_any([&slow, &fast]).await, Some(1));
+/// # })
+/// ```
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+pub fn wait_any<'a, I>(groups: I) -> WaitAny<'a>
+where
+  I: IntoIterator<Item = &'a WaitGroup>,
+{
+  WaitAny {
+    groups: groups.into_iter().map(|wg| (wg, None)).collect(),
+    waiting: Vec::new(),
+  }
+}
+
+/// A future returned by [`wait_all()`].
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitAll<'a> {
+  groups: Vec<&'a WaitGroup>,
+  next: usize,
+  listener: Option<EventListener>,
+  waiting: Option<Waiting<'a>>,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::future::Future for WaitAll<'_> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    while let Some(&wg) = this.groups.get(this.next) {
+      let poll = poll_drained(&wg.inner, &mut this.listener, cx);
+      match wg.inner.waiters.track(&mut this.waiting, poll) {
+        Poll::Ready(()) => this.next += 1,
+        Poll::Pending => return Poll::Pending,
+      }
+    }
+    Poll::Ready(())
+  }
+}
+
+/// A future returned by [`wait_any()`].
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitAny<'a> {
+  groups: Vec<(&'a WaitGroup, Option<EventListener>)>,
+  // Counts the future as a waiter on every group while it is pending.
+  waiting: Vec<Waiting<'a>>,
+}
+
+#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+impl core::future::Future for WaitAny<'_> {
+  type Output = Option<usize>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    if this.groups.is_empty() {
+      return Poll::Ready(None);
+    }
+
+    // Every pending group keeps a listener registered with our waker, so
+    // whichever drains first wakes us.
+    for (idx, (wg, listener)) in this.groups.iter_mut().enumerate() {
+      if poll_drained(&wg.inner, listener, cx).is_ready() {
+        this.waiting.clear();
+        return Poll::Ready(Some(idx));
+      }
+    }
+    if this.waiting.is_empty() {
+      this.waiting = this
+        .groups
+        .iter()
+        .map(|&(wg, _)| wg.inner.waiters.enter())
+        .collect();
+    }
+    Poll::Pending
+  }
+}
@@ -0,0 +1,219 @@
+use core::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use event_listener::{Event, EventListener};
+
+use crate::{
+  phaser::{Core, Notify},
+  primitives::{Arc, Mu},
+};
+
+impl Notify for Event {
+  fn notify_advance(&self) {
+    self.notify(usize::MAX);
+  }
+}
+
+/// An async reusable barrier with a dynamic number of parties and
+/// numbered phases, in the style of Java's `Phaser`.
+///
+/// It has the same phases, registration, termination and tiering rules
+/// as the blocking [`crate::Phaser`]; only waiting is async. Arriving
+/// never blocks, so [`register`](Self::register),
+/// [`arrive`](Self::arrive) and
+/// [`arrive_and_deregister`](Self::arrive_and_deregister) are plain
+/// methods.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::future::Phaser;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let phaser = Phaser::new(1);
+/// for _ in 0..3 {
+///     let p = phaser.clone();
+///     p.register().unwrap();
+///     tokio::spawn(async move {
+///         for _ in 0..2 {
+///             // one step of the simulation
+///             p.arrive_and_await().await;
+///         }
+///         p.arrive_and_deregister();
+///     });
+/// }
+///
+/// assert_eq!(phaser.arrive_and_await().await, 1);
+/// assert_eq!(phaser.arrive_and_await().await, 2);
+/// phaser.arrive_and_deregister();
+/// phaser.await_phase(2).await;
+/// assert!(phaser.is_terminated());
+/// # })
+/// ```
+#[derive(Clone)]
+pub struct Phaser {
+  core: Arc<Core<Event>>,
+}
+
+impl Default for Phaser {
+  fn default() -> Self {
+    Self::new(0)
+  }
+}
+
+impl core::fmt::Debug for Phaser {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Phaser")
+      .field("phase", &self.phase())
+      .field("registered", &self.registered())
+      .field("unarrived", &self.unarrived())
+      .finish()
+  }
+}
+
+impl Phaser {
+  /// Creates a phaser in phase `0` with `parties` registered parties.
+  pub fn new(parties: usize) -> Self {
+    Self {
+      core: Arc::new(Core::new(parties, Event::new())),
+    }
+  }
+
+  /// Creates a child phaser with `parties` registered parties, tiered
+  /// under this one. See [`crate::Phaser::child`].
+  pub fn child(&self, parties: usize) -> Self {
+    Self {
+      core: Arc::new(Core::child(&self.core, parties, Event::new())),
+    }
+  }
+
+  /// Registers a new party in the current phase and returns the phase
+  /// number, or `None` if the phaser has terminated.
+  pub fn register(&self) -> Option<u64> {
+    self.core.register(1)
+  }
+
+  /// Registers `num` new parties at once, like
+  /// [`register`](Self::register).
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of parties would overflow.
+  pub fn register_n(&self, num: usize) -> Option<u64> {
+    self.core.register(num)
+  }
+
+  /// Records the arrival of one party without waiting for the others,
+  /// and returns the phase it arrived in.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every registered party has already arrived in the
+  /// current phase.
+  pub fn arrive(&self) -> u64 {
+    self.core.arrive(false)
+  }
+
+  /// Records the arrival of one party and deregisters it, so the next
+  /// phases no longer wait for it. Returns the phase it arrived in.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every registered party has already arrived in the
+  /// current phase.
+  pub fn arrive_and_deregister(&self) -> u64 {
+    self.core.arrive(true)
+  }
+
+  /// Records the arrival of one party right away, and returns a future
+  /// that resolves to the number of the next phase once every other
+  /// party has arrived as well.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every registered party has already arrived in the
+  /// current phase.
+  pub fn arrive_and_await(&self) -> AwaitPhase<'_> {
+    let phase = self.arrive();
+    self.await_phase(phase)
+  }
+
+  /// Returns a future that resolves once phase `phase` has completed or
+  /// the phaser has terminated, to the current phase number.
+  pub fn await_phase(&self, phase: u64) -> AwaitPhase<'_> {
+    AwaitPhase {
+      root: self.core.root(),
+      phase,
+      listener: None,
+    }
+  }
+
+  /// Returns the current phase number.
+  pub fn phase(&self) -> u64 {
+    self.core.phase()
+  }
+
+  /// Returns the number of parties registered on this phaser. For a
+  /// parent, each child with parties counts as one.
+  pub fn registered(&self) -> usize {
+    self.core.registered()
+  }
+
+  /// Returns the number of registered parties that have not arrived in
+  /// the current phase yet.
+  pub fn unarrived(&self) -> usize {
+    self.core.unarrived()
+  }
+
+  /// Returns `true` once the last party has deregistered.
+  pub fn is_terminated(&self) -> bool {
+    self.core.is_terminated()
+  }
+}
+
+/// A future returned by [`Phaser::await_phase()`] and
+/// [`Phaser::arrive_and_await()`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AwaitPhase<'a> {
+  root: &'a Core<Event>,
+  phase: u64,
+  listener: Option<EventListener>,
+}
+
+impl core::fmt::Debug for AwaitPhase<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("AwaitPhase")
+      .field("phase", &self.phase)
+      .finish()
+  }
+}
+
+impl Future for AwaitPhase<'_> {
+  type Output = u64;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    loop {
+      {
+        let st = this.root.state.lock_me();
+        if st.phase > this.phase || st.terminated {
+          this.listener = None;
+          return Poll::Ready(st.phase);
+        }
+      }
+
+      match this.listener.as_mut() {
+        // Listen, then re-check the phase at the top of the loop to
+        // close the lost-wakeup window.
+        None => this.listener = Some(this.root.notify.listen()),
+        Some(l) => match Pin::new(l).poll(cx) {
+          Poll::Pending => return Poll::Pending,
+          Poll::Ready(()) => this.listener = None,
+        },
+      }
+    }
+  }
+}
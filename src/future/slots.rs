@@ -0,0 +1,262 @@
+use core::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use atomic_waker::AtomicWaker;
+
+use crate::primitives::{AtomicBool, AtomicUsize, Ordering};
+
+/// A waker slot: a claim flag plus the waker of the future holding it.
+struct Slot {
+  claimed: AtomicBool,
+  waker: AtomicWaker,
+}
+
+// An array repeat expression needs a `const` item for non-`Copy` types
+// (inline `const { .. }` blocks are newer than the MSRV). Each use of
+// the item is a fresh value, which is exactly what we want here.
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_SLOT: Slot = Slot {
+  claimed: AtomicBool::new(false),
+  waker: AtomicWaker::new(),
+};
+
+/// An async WaitGroup that needs neither `std` nor `alloc`.
+///
+/// `StaticWaitGroup` has the same `add` / `done` / `wait().await` API as
+/// `future::WaitGroup`, but keeps all of its state inline:
+/// an atomic counter and `N` waker slots. [`new`](Self::new) is `const`,
+/// so it can be placed in a `static` and shared by reference between
+/// tasks — the usual setup on executors such as embassy that run without
+/// a heap.
+///
+/// Each pending [`wait`](Self::wait) future claims one slot for as long
+/// as it is alive and registers its waker there; the `done` that brings
+/// the counter to zero wakes every occupied slot. Up to `N` tasks can
+/// wait at the same time without polling. An additional waiter still
+/// completes correctly, but without a slot it re-schedules itself on
+/// every poll until one frees up or the counter reaches zero.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::future::StaticWaitGroup;
+///
+/// static WG: StaticWaitGroup = StaticWaitGroup::new();
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// for _ in 0..3 {
+///     WG.add(1);
+///     tokio::spawn(async {
+///         // do some work
+///         WG.done();
+///     });
+/// }
+///
+/// WG.wait().await;
+/// assert_eq!(WG.remaining(), 0);
+/// # })
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "atomic-waker")))]
+pub struct StaticWaitGroup<const N: usize = 4> {
+  counter: AtomicUsize,
+  slots: [Slot; N],
+}
+
+impl<const N: usize> Default for StaticWaitGroup<N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<const N: usize> From<usize> for StaticWaitGroup<N> {
+  fn from(count: usize) -> Self {
+    Self {
+      counter: AtomicUsize::new(count),
+      slots: [EMPTY_SLOT; N],
+    }
+  }
+}
+
+impl<const N: usize> core::fmt::Debug for StaticWaitGroup<N> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("StaticWaitGroup")
+      .field("counter", &self.counter)
+      .field("slots", &N)
+      .finish()
+  }
+}
+
+impl<const N: usize> StaticWaitGroup<N> {
+  /// Creates a new `StaticWaitGroup` with a counter of zero.
+  ///
+  /// This is a `const fn`, so the group can be placed in a `static`.
+  pub const fn new() -> Self {
+    Self {
+      counter: AtomicUsize::new(0),
+      slots: [EMPTY_SLOT; N],
+    }
+  }
+
+  /// Increments the counter by `num` and returns `self`, for chaining.
+  ///
+  /// See `future::WaitGroup::add` for the ordering requirements, which
+  /// apply unchanged.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the counter would overflow.
+  pub fn add(&self, num: usize) -> &Self {
+    self
+      .counter
+      .fetch_update(Ordering::Release, Ordering::Relaxed, |prev| {
+        prev.checked_add(num)
+      })
+      .expect("WaitGroup counter overflow");
+    self
+  }
+
+  /// Decrements the counter by one and returns the remaining count.
+  ///
+  /// The call that brings the counter to zero wakes every waiting task.
+  /// If the counter is already zero, this call is a no-op and returns `0`.
+  /// No panic is raised.
+  pub fn done(&self) -> usize {
+    self.done_n(1)
+  }
+
+  /// Decrements the counter by `num` and returns the remaining count.
+  ///
+  /// See `future::WaitGroup::done_n` for the underflow rules, which apply
+  /// unchanged; waiters are woken at most once.
+  pub fn done_n(&self, num: usize) -> usize {
+    // Saturate at zero; an already-zero counter is left untouched.
+    match self
+      .counter
+      .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+        (v != 0).then(|| v.saturating_sub(num))
+      }) {
+      Ok(old) => {
+        let remaining = old.saturating_sub(num);
+        if remaining == 0 {
+          for slot in &self.slots {
+            slot.waker.wake();
+          }
+        }
+        remaining
+      }
+      // Over-done: counter was already zero. Silently no-op.
+      Err(_) => 0,
+    }
+  }
+
+  /// Returns the current counter value — the number of tasks still
+  /// waiting to complete.
+  pub fn remaining(&self) -> usize {
+    self.counter.load(Ordering::Acquire)
+  }
+
+  /// Returns the number of pending [`wait`](Self::wait) futures holding
+  /// a waker slot, at most `N`. A waiter that found every slot taken is
+  /// not counted until it claims one. Like [`remaining`](Self::remaining),
+  /// it is a snapshot.
+  pub fn waiters(&self) -> usize {
+    self
+      .slots
+      .iter()
+      .filter(|slot| slot.claimed.load(Ordering::Relaxed))
+      .count()
+  }
+
+  /// Returns a future that resolves once the counter is zero.
+  pub fn wait(&self) -> StaticWaitGroupFuture<'_, N> {
+    StaticWaitGroupFuture {
+      wg: self,
+      slot: None,
+    }
+  }
+
+  fn claim_slot(&self) -> Option<usize> {
+    self.slots.iter().position(|slot| {
+      slot
+        .claimed
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_ok()
+    })
+  }
+
+  fn release_slot(&self, idx: usize) {
+    let slot = &self.slots[idx];
+    drop(slot.waker.take());
+    slot.claimed.store(false, Ordering::Release);
+  }
+}
+
+/// A future returned by [`StaticWaitGroup::wait()`].
+#[cfg_attr(docsrs, doc(cfg(feature = "atomic-waker")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct StaticWaitGroupFuture<'a, const N: usize> {
+  wg: &'a StaticWaitGroup<N>,
+  slot: Option<usize>,
+}
+
+impl<const N: usize> core::fmt::Debug for StaticWaitGroupFuture<'_, N> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("StaticWaitGroupFuture")
+      .field("wg", self.wg)
+      .field("slot", &self.slot)
+      .finish()
+  }
+}
+
+impl<const N: usize> StaticWaitGroupFuture<'_, N> {
+  fn finish(&mut self) -> Poll<()> {
+    if let Some(idx) = self.slot.take() {
+      self.wg.release_slot(idx);
+    }
+    Poll::Ready(())
+  }
+}
+
+impl<const N: usize> Future for StaticWaitGroupFuture<'_, N> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    if this.wg.counter.load(Ordering::Acquire) == 0 {
+      return this.finish();
+    }
+
+    if this.slot.is_none() {
+      this.slot = this.wg.claim_slot();
+    }
+    match this.slot {
+      Some(idx) => this.wg.slots[idx].waker.register(cx.waker()),
+      // Every slot is taken: nobody would wake us, so ask to be polled
+      // again.
+      None => {
+        cx.waker().wake_by_ref();
+        return Poll::Pending;
+      }
+    }
+
+    // Re-check after registering to close the lost-wakeup window: a
+    // `done()` that hit zero before the waker was stored has already
+    // run its wake-ups.
+    if this.wg.counter.load(Ordering::Acquire) == 0 {
+      this.finish()
+    } else {
+      Poll::Pending
+    }
+  }
+}
+
+impl<const N: usize> Drop for StaticWaitGroupFuture<'_, N> {
+  fn drop(&mut self) {
+    if let Some(idx) = self.slot.take() {
+      self.wg.release_slot(idx);
+    }
+  }
+}
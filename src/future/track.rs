@@ -0,0 +1,91 @@
+use core::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use super::Token;
+
+pin_project_lite::pin_project! {
+    /// A future returned by [`WaitGroup::track()`](super::WaitGroup::track).
+    ///
+    /// It counts as one unit on the group from its creation until the
+    /// wrapped future completes or the wrapper is dropped.
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct Tracked<F> {
+        #[pin]
+        fut: F,
+        token: Option<Token>,
+    }
+}
+
+impl<F> Tracked<F> {
+  pub(super) fn new(fut: F, token: Token) -> Self {
+    Self {
+      fut,
+      token: Some(token),
+    }
+  }
+}
+
+impl<F: Future> Future for Tracked<F> {
+  type Output = F::Output;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.project();
+    let out = core::task::ready!(this.fut.poll(cx));
+    // Complete the unit as soon as the output is ready, rather than
+    // whenever the wrapper happens to be dropped.
+    if let Some(token) = this.token.take() {
+      token.done();
+    }
+    Poll::Ready(out)
+  }
+}
+
+#[cfg(feature = "stream")]
+pin_project_lite::pin_project! {
+    /// A stream returned by
+    /// [`WaitGroup::track_stream()`](super::WaitGroup::track_stream).
+    ///
+    /// It counts as one unit on the group from its creation until the
+    /// wrapped stream is exhausted or the wrapper is dropped.
+    #[derive(Debug)]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TrackedStream<S> {
+        #[pin]
+        stream: S,
+        token: Option<Token>,
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<S> TrackedStream<S> {
+  pub(super) fn new(stream: S, token: Token) -> Self {
+    Self {
+      stream,
+      token: Some(token),
+    }
+  }
+}
+
+#[cfg(feature = "stream")]
+impl<S: futures_core::Stream> futures_core::Stream for TrackedStream<S> {
+  type Item = S::Item;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = self.project();
+    let item = core::task::ready!(this.stream.poll_next(cx));
+    if item.is_none() {
+      if let Some(token) = this.token.take() {
+        token.done();
+      }
+    }
+    Poll::Ready(item)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.stream.size_hint()
+  }
+}
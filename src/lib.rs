@@ -30,14 +30,60 @@ extern crate alloc as std;
 #[cfg(feature = "std")]
 extern crate std;
 
-/// A WaitGroup that can be used in async contexts. See [`future::WaitGroup`] for details.
-#[cfg(all(any(feature = "std", feature = "alloc"), feature = "future"))]
+mod primitives;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod waiters;
+
+/// WaitGroups that can be used in async contexts. See `future::WaitGroup`
+/// for details, or `future::StaticWaitGroup` (with the `atomic-waker`
+/// feature) for the allocation-free form.
+#[cfg(any(
+  all(any(feature = "std", feature = "alloc"), feature = "future"),
+  feature = "atomic-waker"
+))]
 #[cfg_attr(
   docsrs,
-  doc(cfg(all(any(feature = "std", feature = "alloc"), feature = "future")))
+  doc(cfg(any(
+    all(any(feature = "std", feature = "alloc"), feature = "future"),
+    feature = "atomic-waker"
+  )))
 )]
 pub mod future;
 
+/// A graceful-shutdown coordinator: a trigger, tracked tasks, a grace
+/// period and an escalation callback. See [`shutdown::Shutdown`] for
+/// details.
+#[cfg(all(feature = "std", feature = "future", not(loom)))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "future"))))]
+pub mod shutdown;
+
+/// A fixed-size thread pool whose jobs are tracked by a [`WaitGroup`].
+/// See [`pool::ThreadPool`] for details.
+#[cfg(all(feature = "std", not(loom)))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod pool;
+
+/// Data-parallel `for_each` and `map` on scoped threads, coordinated by a
+/// [`WaitGroup`]. See [`parallel::for_each`] and [`parallel::map`].
+#[cfg(all(feature = "std", not(loom)))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod parallel;
+
+/// An interrupt-safe WaitGroup for single-core microcontrollers. See
+/// [`cs::WaitGroup`] for details.
+#[cfg(feature = "critical-section")]
+#[cfg_attr(docsrs, doc(cfg(feature = "critical-section")))]
+pub mod cs;
+
+#[cfg(feature = "std")]
+mod phaser;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod seal;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use seal::{Sealed, WaitOutcome};
+
 #[cfg(feature = "std")]
 mod sync;
 #[cfg(feature = "std")]
@@ -46,12 +92,18 @@ pub use sync::*;
 /// A lock-free, atomic-counter WaitGroup that spins on `wait`.
 ///
 /// Available in both `std` and `no_std` environments. See
-/// [`spin::WaitGroup`] for details.
-#[cfg(any(feature = "std", feature = "alloc"))]
-#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// `spin::WaitGroup` for details, or [`spin::WaitGroupRef`] for the
+/// allocation-free form usable in pure `core`.
 pub mod spin;
 
+#[cfg(all(
+  not(feature = "std"),
+  feature = "alloc",
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+pub use spin::WeakWaitGroup;
 /// In `no_std` builds, `WaitGroup` is an alias for [`spin::WaitGroup`].
 /// In `std` builds, `WaitGroup` is the `Mutex`/`Condvar`-based variant.
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
-pub use spin::WaitGroup;
+pub use spin::{AutoWaitGroup, Token, WaitGroup};
@@ -0,0 +1,112 @@
+//! Data-parallel loops on scoped threads, without a global pool.
+
+use std::{thread, vec::Vec};
+
+use crate::WaitGroup;
+
+/// Calls `f` on every item of `iter`, spread over `threads` scoped
+/// threads, and returns once all calls have finished.
+///
+/// The items are collected first and split into one contiguous chunk per
+/// thread, so `f` runs about `len / threads` times on each. `threads == 0`
+/// uses [`std::thread::available_parallelism`], and no more threads than
+/// items are spawned; with a single chunk, `f` runs on the calling thread.
+///
+/// # Panics
+///
+/// If `f` panics, the remaining chunks still run to completion, then the
+/// first panic is resumed on the calling thread with its original
+/// payload.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let sum = AtomicUsize::new(0);
+/// wg::parallel::for_each(1..=100, 4, |n| {
+///     sum.fetch_add(n, Ordering::Relaxed);
+/// });
+/// assert_eq!(sum.into_inner(), 5050);
+/// ```
+pub fn for_each<I, F>(iter: I, threads: usize, f: F)
+where
+  I: IntoIterator,
+  I::Item: Send,
+  F: Fn(I::Item) + Sync,
+{
+  run_chunks(iter.into_iter().collect(), threads, |chunk| {
+    chunk.into_iter().for_each(&f)
+  });
+}
+
+/// Applies `f` to every item of `iter`, spread over `threads` scoped
+/// threads, and returns the results in the order of the items.
+///
+/// Chunking, the meaning of `threads` and panic propagation are the same
+/// as for [`for_each`].
+///
+/// # Example
+///
+/// ```rust
+/// let lens = wg::parallel::map(["a", "bb", "ccc"], 2, str::len);
+/// assert_eq!(lens, [1, 2, 3]);
+/// ```
+pub fn map<I, F, R>(iter: I, threads: usize, f: F) -> Vec<R>
+where
+  I: IntoIterator,
+  I::Item: Send,
+  F: Fn(I::Item) -> R + Sync,
+  R: Send,
+{
+  run_chunks(iter.into_iter().collect(), threads, |chunk| {
+    chunk.into_iter().map(&f).collect::<Vec<_>>()
+  })
+  .into_iter()
+  .flatten()
+  .collect()
+}
+
+/// Splits `items` into contiguous chunks, runs `work` on each on its own
+/// scoped thread, and returns the outputs in chunk order.
+fn run_chunks<T, O, W>(items: Vec<T>, threads: usize, work: W) -> Vec<O>
+where
+  T: Send,
+  O: Send,
+  W: Fn(Vec<T>) -> O + Sync,
+{
+  let threads = match threads {
+    0 => thread::available_parallelism().map_or(1, |n| n.get()),
+    n => n,
+  };
+  let len = items.len();
+  if threads <= 1 || len <= 1 {
+    return std::vec![work(items)];
+  }
+
+  let size = len.div_ceil(threads.min(len));
+  let chunks = len.div_ceil(size);
+  let mut items = items.into_iter();
+  // One slot per chunk, written by its thread, so the outputs keep the
+  // order of the items whichever thread finishes first.
+  let mut out: Vec<Option<O>> = (0..chunks).map(|_| None).collect();
+
+  let wg = WaitGroup::new();
+  let joined = thread::scope(|s| {
+    let work = &work;
+    for slot in out.iter_mut() {
+      let chunk: Vec<T> = items.by_ref().take(size).collect();
+      let t_wg = wg.add(1);
+      s.spawn(move || *slot = t_wg.run(|| work(chunk)));
+    }
+    wg.join()
+  });
+  if let Err(panicked) = joined {
+    panicked.resume();
+  }
+
+  out
+    .into_iter()
+    .map(|o| o.expect("every chunk completed"))
+    .collect()
+}
@@ -0,0 +1,187 @@
+//! The phase bookkeeping shared by the blocking and async `Phaser`s.
+//!
+//! A tiered phaser is a tree. Each non-root node counts as one party of
+//! its parent while it has parties of its own, and arrives there once all
+//! of them have arrived, so a large party count only ever contends on
+//! small nodes. The phase number lives in the root; the other nodes catch
+//! up with it lazily, the next time one of their parties touches them.
+//!
+//! Locks are only ever taken from a node towards the root, never the
+//! other way, so nested locking cannot deadlock.
+
+use crate::primitives::{Arc, Mu, Mutex};
+
+/// Wakes everything waiting on a root phaser when its phase advances.
+pub(crate) trait Notify {
+  fn notify_advance(&self);
+}
+
+#[derive(Debug)]
+pub(crate) struct State {
+  pub(crate) phase: u64,
+  parties: usize,
+  unarrived: usize,
+  /// Parties registered on a non-root node after its last unarrived
+  /// party had arrived, i.e. after the node itself had arrived at its
+  /// parent. Until they arrive, each is registered with the parent
+  /// directly, so the phase cannot advance without them.
+  late: usize,
+  /// Only ever set on the root.
+  pub(crate) terminated: bool,
+}
+
+pub(crate) struct Core<N> {
+  pub(crate) state: Mutex<State>,
+  pub(crate) notify: N,
+  parent: Option<Arc<Core<N>>>,
+}
+
+impl<N: Notify> Core<N> {
+  pub(crate) fn new(parties: usize, notify: N) -> Self {
+    Self {
+      state: Mutex::new(State {
+        phase: 0,
+        parties,
+        unarrived: parties,
+        late: 0,
+        terminated: false,
+      }),
+      notify,
+      parent: None,
+    }
+  }
+
+  pub(crate) fn child(parent: &Arc<Self>, parties: usize, notify: N) -> Self {
+    let mut core = Self::new(0, notify);
+    core.parent = Some(parent.clone());
+    core.register(parties);
+    core
+  }
+
+  /// The node holding the phase number and the waiters.
+  pub(crate) fn root(&self) -> &Self {
+    let mut node = self;
+    while let Some(parent) = &node.parent {
+      node = parent;
+    }
+    node
+  }
+
+  /// Catches a non-root node up with the root, if it has advanced since
+  /// the node last looked.
+  fn reconcile(&self, st: &mut State) {
+    if self.parent.is_none() || st.parties == 0 {
+      return;
+    }
+    let phase = self.root().state.lock_me().phase;
+    if phase > st.phase {
+      st.phase = phase;
+      st.unarrived = st.parties;
+      st.late = 0;
+    }
+  }
+
+  pub(crate) fn phase(&self) -> u64 {
+    self.root().state.lock_me().phase
+  }
+
+  pub(crate) fn is_terminated(&self) -> bool {
+    self.root().state.lock_me().terminated
+  }
+
+  pub(crate) fn registered(&self) -> usize {
+    self.state.lock_me().parties
+  }
+
+  pub(crate) fn unarrived(&self) -> usize {
+    let mut st = self.state.lock_me();
+    self.reconcile(&mut st);
+    st.unarrived + st.late
+  }
+
+  /// Registers `n` parties in the current phase and returns it, or
+  /// `None` once the phaser has terminated.
+  pub(crate) fn register(&self, n: usize) -> Option<u64> {
+    let mut st = self.state.lock_me();
+    let Some(parent) = &self.parent else {
+      if st.terminated {
+        return None;
+      }
+      st.parties = st
+        .parties
+        .checked_add(n)
+        .expect("Phaser party count overflow");
+      st.unarrived += n;
+      return Some(st.phase);
+    };
+
+    self.reconcile(&mut st);
+    if st.parties == 0 {
+      if n == 0 {
+        return parent.register(0);
+      }
+      // The node joins its parent as a single party.
+      st.phase = parent.register(1)?;
+      st.parties = n;
+      st.unarrived = n;
+      return Some(st.phase);
+    }
+
+    let parties = st
+      .parties
+      .checked_add(n)
+      .expect("Phaser party count overflow");
+    let phase = if st.unarrived == 0 {
+      // This node already arrived at its parent for the current phase.
+      let phase = parent.register(n)?;
+      st.late += n;
+      phase
+    } else {
+      st.unarrived += n;
+      st.phase
+    };
+    st.parties = parties;
+    Some(phase)
+  }
+
+  /// Records the arrival of one party, deregistering it if `deregister`,
+  /// and returns the phase it arrived in.
+  pub(crate) fn arrive(&self, deregister: bool) -> u64 {
+    let mut st = self.state.lock_me();
+    self.reconcile(&mut st);
+    assert!(
+      st.unarrived + st.late != 0,
+      "Phaser arrivals exceed its registered parties"
+    );
+    let phase = st.phase;
+    if deregister {
+      st.parties -= 1;
+    }
+
+    let Some(parent) = &self.parent else {
+      st.unarrived -= 1;
+      if st.unarrived == 0 {
+        st.phase += 1;
+        st.unarrived = st.parties;
+        st.terminated = st.parties == 0;
+        self.notify.notify_advance();
+      }
+      return phase;
+    };
+
+    if st.unarrived == 0 {
+      // A late party: it arrives at the parent directly, and leaves it
+      // again, as it joins this node's own count from the next phase.
+      // The parties that arrived before it keep `parties` above `late`,
+      // so the node stays registered at the parent for that phase.
+      st.late -= 1;
+      parent.arrive(true);
+    } else {
+      st.unarrived -= 1;
+      if st.unarrived == 0 {
+        parent.arrive(st.parties == 0);
+      }
+    }
+    phase
+  }
+}
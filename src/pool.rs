@@ -0,0 +1,285 @@
+//! A fixed-size thread pool whose jobs are counted on a [`WaitGroup`].
+
+use std::{
+  boxed::Box,
+  format, io,
+  string::String,
+  sync::mpsc,
+  thread::{self, JoinHandle},
+  vec::Vec,
+};
+
+use crate::{
+  primitives::{Arc, Mu, Mutex},
+  WaitGroup, WorkerPanicked,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+enum Queue {
+  Bounded(mpsc::SyncSender<Job>),
+  Unbounded(mpsc::Sender<Job>),
+}
+
+impl Queue {
+  /// Hands `job` to the workers, giving it back if they are all gone.
+  fn send(&self, job: Job) -> Result<(), Job> {
+    match self {
+      Self::Bounded(tx) => tx.send(job).map_err(|e| e.0),
+      Self::Unbounded(tx) => tx.send(job).map_err(|e| e.0),
+    }
+  }
+}
+
+/// Configures and spawns a [`ThreadPool`].
+///
+/// # Example
+///
+/// ```rust
+/// use wg::{pool::Builder, WaitGroup};
+///
+/// let wg = WaitGroup::new();
+/// let pool = Builder::new()
+///     .size(4)
+///     .thread_name("indexer")
+///     .queue_capacity(64)
+///     .wait_group(wg.clone())
+///     .build()
+///     .unwrap();
+///
+/// for _ in 0..100 {
+///     pool.execute(|| {
+///         // index one document
+///     });
+/// }
+///
+/// // The jobs are counted on the caller's group.
+/// wg.wait();
+/// ```
+#[derive(Debug, Default)]
+pub struct Builder {
+  size: Option<usize>,
+  name: Option<String>,
+  capacity: Option<usize>,
+  wg: Option<WaitGroup>,
+}
+
+impl Builder {
+  /// Creates a builder with the defaults: one worker per available CPU,
+  /// threads named `wg-pool-{i}`, an unbounded queue and an internal
+  /// [`WaitGroup`].
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the number of worker threads. Must be non-zero.
+  pub fn size(mut self, size: usize) -> Self {
+    self.size = Some(size);
+    self
+  }
+
+  /// Names the worker threads `{prefix}-{i}`.
+  pub fn thread_name(mut self, prefix: impl Into<String>) -> Self {
+    self.name = Some(prefix.into());
+    self
+  }
+
+  /// Bounds the queue of jobs waiting for a worker.
+  /// [`execute`](ThreadPool::execute) blocks while the queue is full. With
+  /// a capacity of `0`, it blocks until a worker takes the job.
+  pub fn queue_capacity(mut self, capacity: usize) -> Self {
+    self.capacity = Some(capacity);
+    self
+  }
+
+  /// Counts the pool's jobs on `wg` instead of an internal group, so the
+  /// caller can wait for them together with other work.
+  pub fn wait_group(mut self, wg: WaitGroup) -> Self {
+    self.wg = Some(wg);
+    self
+  }
+
+  /// Spawns the worker threads.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the size is zero or a thread cannot be spawned.
+  pub fn build(self) -> io::Result<ThreadPool> {
+    let size = match self.size {
+      Some(0) => {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "thread pool size must be non-zero",
+        ))
+      }
+      Some(size) => size,
+      None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    let (queue, rx) = match self.capacity {
+      Some(capacity) => {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        (Queue::Bounded(tx), rx)
+      }
+      None => {
+        let (tx, rx) = mpsc::channel();
+        (Queue::Unbounded(tx), rx)
+      }
+    };
+    let rx = Arc::new(Mutex::new(rx));
+    let prefix = self.name.as_deref().unwrap_or("wg-pool");
+
+    // On error, dropping `pool` closes the queue and joins the workers
+    // spawned so far.
+    let mut pool = ThreadPool {
+      queue: Some(queue),
+      workers: Vec::with_capacity(size),
+      wg: self.wg.unwrap_or_default(),
+    };
+    for i in 0..size {
+      let rx = rx.clone();
+      let worker = thread::Builder::new()
+        .name(format!("{prefix}-{i}"))
+        .spawn(move || work(&rx))?;
+      pool.workers.push(worker);
+    }
+    Ok(pool)
+  }
+}
+
+fn work(rx: &Mutex<mpsc::Receiver<Job>>) {
+  loop {
+    // Only hold the lock while taking a job, not while running it.
+    let job = rx.lock_me().recv();
+    match job {
+      Ok(job) => job(),
+      // The pool was dropped and the queue is drained.
+      Err(_) => break,
+    }
+  }
+}
+
+/// A fixed-size pool of worker threads whose jobs are counted on a
+/// [`WaitGroup`].
+///
+/// Every job passed to [`execute`](Self::execute) adds one unit to the
+/// group, and completes it when the job returns. A job that panics is
+/// isolated through [`WaitGroup::run`]: the worker survives, and the
+/// panic is reported by [`join`](Self::join).
+///
+/// Dropping the pool lets the workers finish the jobs already queued,
+/// then joins them.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use wg::pool::ThreadPool;
+///
+/// let pool = ThreadPool::new(4);
+/// let ctr = Arc::new(AtomicUsize::new(0));
+///
+/// for _ in 0..8 {
+///     let ctr = ctr.clone();
+///     pool.execute(move || {
+///         ctr.fetch_add(1, Ordering::Relaxed);
+///     });
+/// }
+///
+/// pool.join().unwrap();
+/// assert_eq!(ctr.load(Ordering::Relaxed), 8);
+/// ```
+pub struct ThreadPool {
+  queue: Option<Queue>,
+  workers: Vec<JoinHandle<()>>,
+  wg: WaitGroup,
+}
+
+impl core::fmt::Debug for ThreadPool {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("ThreadPool")
+      .field("size", &self.workers.len())
+      .field("wait_group", &self.wg)
+      .finish()
+  }
+}
+
+impl ThreadPool {
+  /// Spawns a pool of `size` workers with the default settings of
+  /// [`Builder`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if `size` is zero or a thread cannot be spawned; use
+  /// [`Builder::build`] to handle those errors.
+  pub fn new(size: usize) -> Self {
+    Builder::new()
+      .size(size)
+      .build()
+      .expect("failed to spawn thread pool")
+  }
+
+  /// Returns a [`Builder`] to configure a pool.
+  pub fn builder() -> Builder {
+    Builder::new()
+  }
+
+  /// Returns the number of worker threads.
+  pub fn size(&self) -> usize {
+    self.workers.len()
+  }
+
+  /// Returns the [`WaitGroup`] the jobs are counted on.
+  pub fn wait_group(&self) -> &WaitGroup {
+    &self.wg
+  }
+
+  /// Queues `job` to run on one of the workers, counting it on the pool's
+  /// [`WaitGroup`] until it returns.
+  ///
+  /// Blocks while a bounded queue is full.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the pool's [`WaitGroup`] has been
+  /// [sealed](WaitGroup::seal), for example by the code that passed it to
+  /// [`Builder::wait_group`].
+  pub fn execute<F>(&self, job: F)
+  where
+    F: FnOnce() + Send + 'static,
+  {
+    let wg = self.wg.add(1);
+    let job: Job = Box::new(move || {
+      wg.run(job);
+    });
+
+    let queue = self.queue.as_ref().expect("queue is open until drop");
+    if let Err(job) = queue.send(job) {
+      // Workers catch job panics and only exit once the queue is closed,
+      // so this cannot happen; still, don't leave the unit behind.
+      drop(job);
+      self.wg.done();
+      unreachable!("thread pool workers exited early");
+    }
+  }
+
+  /// Blocks until every queued and running job has finished.
+  ///
+  /// Returns `Err(WorkerPanicked)` if a job panicked since the last
+  /// `join`, see [`WaitGroup::join`]. With a caller-supplied group, it
+  /// also waits for the other work counted on it.
+  pub fn join(&self) -> Result<(), WorkerPanicked> {
+    self.wg.join()
+  }
+}
+
+impl Drop for ThreadPool {
+  fn drop(&mut self) {
+    // Closing the queue makes each worker exit once it is drained.
+    drop(self.queue.take());
+    for worker in self.workers.drain(..) {
+      let _ = worker.join();
+    }
+  }
+}
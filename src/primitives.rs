@@ -0,0 +1,153 @@
+//! The synchronization primitives the WaitGroup variants are built on.
+//!
+//! Every variant imports its atomics, `Arc`, `Mutex`, `Condvar` and
+//! thread parking from here. Under `--cfg loom` they are swapped for
+//! [`loom`]'s model-checked equivalents, so the tests in `tests/loom.rs`
+//! can explore every interleaving of `add`, `done` and `wait`. With the `portable-atomic`
+//! feature, the atomics and `Arc` come from [`portable-atomic`] instead,
+//! so the lock-free variants build on targets without compare-and-swap.
+//!
+//! [`loom`]: https://docs.rs/loom
+//! [`portable-atomic`]: https://docs.rs/portable-atomic
+
+// `pub` (in a private module) so `spin` can re-export `AtomicUsize`.
+// `Ordering` goes unused on targets without compare-and-swap, where the
+// counter operations are not built.
+#[cfg(all(
+  not(loom),
+  not(feature = "portable-atomic"),
+  any(feature = "atomic-waker", feature = "std")
+))]
+pub(crate) use core::sync::atomic::AtomicBool;
+#[cfg(all(not(loom), not(feature = "portable-atomic")))]
+#[allow(unused_imports)]
+pub use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(all(loom, feature = "std"))]
+pub(crate) use loom::sync::atomic::AtomicBool;
+#[cfg(loom)]
+pub use loom::sync::atomic::{fence, AtomicUsize, Ordering};
+#[cfg(all(
+  not(loom),
+  feature = "portable-atomic",
+  any(feature = "atomic-waker", feature = "std")
+))]
+pub(crate) use portable_atomic::AtomicBool;
+#[cfg(all(not(loom), feature = "portable-atomic"))]
+pub use portable_atomic::{AtomicUsize, Ordering};
+
+// Only the seal of the `Arc`-backed variants needs a fence.
+#[cfg(all(
+  not(loom),
+  not(feature = "portable-atomic"),
+  any(feature = "std", feature = "alloc")
+))]
+pub(crate) use core::sync::atomic::fence;
+#[cfg(all(
+  not(loom),
+  feature = "portable-atomic",
+  any(feature = "std", feature = "alloc")
+))]
+pub(crate) use portable_atomic::fence;
+
+// `portable-atomic` wins over `triomphe`: `triomphe::Arc` needs native
+// compare-and-swap, which is exactly what targets using that feature lack.
+// `Weak` only exists with the `Arc`s that keep a weak count: neither
+// `triomphe::Arc` nor loom's has one.
+#[cfg(loom)]
+pub(crate) use loom::sync::Arc;
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  feature = "portable-atomic"
+))]
+pub(crate) use portable_atomic_util::{Arc, Weak};
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  not(feature = "portable-atomic"),
+  not(feature = "triomphe")
+))]
+pub(crate) use std::sync::{Arc, Weak};
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  not(feature = "portable-atomic"),
+  feature = "triomphe"
+))]
+pub(crate) use triomphe::Arc;
+
+// `Condvar` is used by the `Mutex` backend of the blocking `WaitGroup`
+// and by the blocking `Phaser`.
+#[cfg(all(feature = "std", loom))]
+pub(crate) use loom::sync::{Condvar, Mutex};
+#[cfg(all(feature = "std", not(loom), feature = "parking_lot"))]
+#[allow(unused_imports)]
+pub(crate) use parking_lot::{Condvar, Mutex};
+#[cfg(all(feature = "std", not(loom), not(feature = "parking_lot")))]
+#[allow(unused_imports)]
+pub(crate) use std::sync::{Condvar, Mutex};
+
+#[cfg(all(feature = "std", loom))]
+pub(crate) use loom::thread;
+#[cfg(all(feature = "std", not(loom)))]
+pub(crate) use std::thread;
+
+/// Locks a `Mutex` the same way whichever implementation is in use,
+/// recovering from poisoning.
+#[cfg(feature = "std")]
+pub(crate) trait Mu {
+  type Guard<'a>
+  where
+    Self: 'a;
+  fn lock_me(&self) -> Self::Guard<'_>;
+}
+
+#[cfg(all(feature = "std", feature = "parking_lot", not(loom)))]
+impl<T: ?Sized> Mu for parking_lot::Mutex<T> {
+  type Guard<'a>
+    = parking_lot::MutexGuard<'a, T>
+  where
+    Self: 'a;
+
+  fn lock_me(&self) -> Self::Guard<'_> {
+    self.lock()
+  }
+}
+
+#[cfg(all(feature = "std", not(feature = "parking_lot"), not(loom)))]
+impl<T: ?Sized> Mu for std::sync::Mutex<T> {
+  type Guard<'a>
+    = std::sync::MutexGuard<'a, T>
+  where
+    Self: 'a;
+
+  fn lock_me(&self) -> Self::Guard<'_> {
+    // Poisoning is not meaningful for a `usize` counter: the worst a
+    // panicking thread can leave behind is a stale count, not corrupt
+    // memory. Recovering the guard avoids cascading panics across all
+    // other threads that touch this WaitGroup.
+    self.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+#[cfg(all(feature = "std", loom))]
+impl<T> Mu for loom::sync::Mutex<T> {
+  type Guard<'a>
+    = loom::sync::MutexGuard<'a, T>
+  where
+    Self: 'a;
+
+  fn lock_me(&self) -> Self::Guard<'_> {
+    self.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+/// Lets a spin loop make progress under loom, which otherwise sees a
+/// thread that never yields and gives up on the execution. A no-op in
+/// regular builds.
+#[allow(dead_code)]
+#[inline(always)]
+pub(crate) fn spin_yield() {
+  #[cfg(loom)]
+  loom::thread::yield_now();
+}
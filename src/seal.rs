@@ -0,0 +1,73 @@
+//! Sealing and resetting, shared by the `Arc`-backed `WaitGroup` variants.
+
+use crate::primitives::{fence, AtomicUsize, Ordering};
+
+/// The error returned by `try_add` once a `WaitGroup` has been sealed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sealed;
+
+impl core::fmt::Display for Sealed {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.write_str("the WaitGroup is sealed")
+  }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Sealed {}
+
+/// How a `wait_outcome` call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WaitOutcome {
+  /// The counter drained to zero.
+  Drained,
+  /// The group was reset while the caller was waiting.
+  Reset,
+}
+
+/// The seal of a group whose counter is a bare atomic.
+///
+/// `add` reads the seal before touching the counter, so adding to a
+/// group sealed earlier fails without disturbing it. Only an `add` that
+/// races with `seal` increments and then finds the seal; the check after
+/// the increment, with a `SeqCst` fence on both sides, makes an `add`
+/// that misses the seal visible to everything the sealing thread does
+/// next, such as waiting for the group.
+#[derive(Debug)]
+pub(crate) struct Seal(AtomicUsize);
+
+impl Seal {
+  pub(crate) fn new() -> Self {
+    Self(AtomicUsize::new(0))
+  }
+
+  pub(crate) fn seal(&self) {
+    self.0.store(1, Ordering::Relaxed);
+    fence(Ordering::SeqCst);
+  }
+
+  pub(crate) fn is_sealed(&self) -> bool {
+    self.0.load(Ordering::Relaxed) != 0
+  }
+
+  /// Called before `add` touches the counter.
+  pub(crate) fn precheck(&self) -> Result<(), Sealed> {
+    if self.is_sealed() {
+      Err(Sealed)
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Called right after `add` incremented the counter. On `Err`, the
+  /// caller must take its units back through its `done` path: a waiter
+  /// may already be asleep on those units, so taking back the last of
+  /// them has to wake it like any other drain.
+  pub(crate) fn check(&self) -> Result<(), Sealed> {
+    fence(Ordering::SeqCst);
+    if self.is_sealed() {
+      Err(Sealed)
+    } else {
+      Ok(())
+    }
+  }
+}
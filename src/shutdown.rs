@@ -0,0 +1,382 @@
+use core::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+use std::boxed::Box;
+
+use event_listener::{Event, EventListener};
+
+use crate::{
+  future::WaitGroup,
+  primitives::{Arc, AtomicBool, Mu, Mutex, Ordering},
+};
+
+type Escalation = Box<dyn FnOnce() + Send>;
+
+struct Inner {
+  triggered: AtomicBool,
+  event: Event,
+  tasks: WaitGroup,
+  escalation: Mutex<Option<Escalation>>,
+}
+
+impl Inner {
+  fn is_triggered(&self) -> bool {
+    self.triggered.load(Ordering::Acquire)
+  }
+}
+
+/// How a [`Shutdown::drain`] or [`Shutdown::drain_blocking`] call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+  /// Every registered task finished within the grace period.
+  Drained,
+  /// The grace period expired first and the escalation callback, if any,
+  /// was run. `remaining` is the number of tasks still registered at that
+  /// point.
+  Escalated {
+    /// Tasks that had not finished when the grace period expired.
+    remaining: usize,
+  },
+}
+
+/// Coordinates the graceful shutdown of a set of tasks, on top of a
+/// [`future::WaitGroup`](WaitGroup).
+///
+/// It ties together the pieces every service ends up writing by hand:
+///
+/// - a **trigger** that workers watch ([`ShutdownGuard::triggered`]),
+/// - the **tasks** still running, counted through
+///   [`register`](Shutdown::register) guards,
+/// - a **grace period** to let them drain ([`Shutdown::drain`],
+///   [`Shutdown::drain_blocking`]),
+/// - and an **escalation** callback that runs when the grace period
+///   expires first ([`Shutdown::with_escalation`]).
+///
+/// With the `signal` feature, `Shutdown::trigger_on_signals` fires the
+/// trigger on `SIGINT` / `SIGTERM` on Unix.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use wg::shutdown::{Outcome, Shutdown};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let shutdown = Shutdown::new();
+///
+/// for _ in 0..3 {
+///     let guard = shutdown.register();
+///     tokio::spawn(async move {
+///         // Work until asked to stop; dropping the guard reports this task
+///         // as finished.
+///         guard.triggered().await;
+///     });
+/// }
+///
+/// let outcome = shutdown.drain(tokio::time::sleep(Duration::from_secs(5))).await;
+/// assert_eq!(outcome, Outcome::Drained);
+/// # })
+/// ```
+///
+/// Cloning a `Shutdown` gives another handle to the same trigger, task
+/// count and escalation callback.
+#[derive(Clone)]
+pub struct Shutdown {
+  inner: Arc<Inner>,
+}
+
+impl Default for Shutdown {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl core::fmt::Debug for Shutdown {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Shutdown")
+      .field("triggered", &self.is_triggered())
+      .field("remaining", &self.remaining())
+      .finish()
+  }
+}
+
+impl Shutdown {
+  /// Creates a new, untriggered `Shutdown` with no registered tasks and no
+  /// escalation callback.
+  pub fn new() -> Self {
+    Self {
+      inner: Arc::new(Inner {
+        triggered: AtomicBool::new(false),
+        event: Event::new(),
+        tasks: WaitGroup::new(),
+        escalation: Mutex::new(None),
+      }),
+    }
+  }
+
+  /// Sets the callback run when a drain's grace period expires before every
+  /// task has finished, typically to abort the stragglers or exit the
+  /// process. It runs at most once, and replaces any earlier callback.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use std::time::Duration;
+  /// use wg::shutdown::{Outcome, Shutdown};
+  ///
+  /// let shutdown = Shutdown::new().with_escalation(|| eprintln!("forcing shutdown"));
+  /// let _stuck = shutdown.register();
+  ///
+  /// let outcome = shutdown.drain_blocking(Duration::from_millis(10));
+  /// assert_eq!(outcome, Outcome::Escalated { remaining: 1 });
+  /// ```
+  pub fn with_escalation<F>(self, f: F) -> Self
+  where
+    F: FnOnce() + Send + 'static,
+  {
+    *self.inner.escalation.lock_me() = Some(Box::new(f));
+    self
+  }
+
+  /// Registers a task, returning a guard that reports it as finished when
+  /// dropped.
+  ///
+  /// Tasks registered after the trigger still count: a drain waits for
+  /// them too.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the [`wait_group`](Self::wait_group) has been
+  /// [sealed](WaitGroup::seal).
+  pub fn register(&self) -> ShutdownGuard {
+    self.inner.tasks.add(1);
+    ShutdownGuard {
+      inner: self.inner.clone(),
+    }
+  }
+
+  /// Returns the number of registered tasks that have not finished yet.
+  pub fn remaining(&self) -> usize {
+    self.inner.tasks.remaining()
+  }
+
+  /// Returns the [`WaitGroup`] counting the registered tasks, for code
+  /// that already takes one.
+  pub fn wait_group(&self) -> &WaitGroup {
+    &self.inner.tasks
+  }
+
+  /// Fires the trigger, waking everything waiting on
+  /// [`triggered`](Self::triggered). Calling it again has no effect.
+  pub fn trigger(&self) {
+    if !self.inner.triggered.swap(true, Ordering::AcqRel) {
+      self.inner.event.notify(usize::MAX);
+    }
+  }
+
+  /// Returns `true` once the trigger has fired.
+  pub fn is_triggered(&self) -> bool {
+    self.inner.is_triggered()
+  }
+
+  /// Returns a future that resolves once the trigger has fired.
+  pub fn triggered(&self) -> Triggered<'_> {
+    Triggered {
+      inner: &self.inner,
+      listener: None,
+    }
+  }
+
+  /// Blocks the current thread until the trigger has fired.
+  ///
+  /// Do not call it from inside an async task; await
+  /// [`triggered`](Self::triggered) instead.
+  #[cfg(not(target_family = "wasm"))]
+  #[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+  pub fn wait_triggered(&self) {
+    use event_listener::Listener;
+
+    while !self.is_triggered() {
+      let ln = self.inner.event.listen();
+      if self.is_triggered() {
+        return;
+      }
+      ln.wait();
+    }
+  }
+
+  /// Fires the trigger, then waits for every registered task to finish or
+  /// for `grace` to resolve, whichever comes first. If `grace` wins, the
+  /// escalation callback runs before this returns.
+  ///
+  /// `grace` is any future, so the timer comes from whichever runtime is in
+  /// use, e.g. `tokio::time::sleep(grace_period)`.
+  pub async fn drain<G>(&self, grace: G) -> Outcome
+  where
+    G: Future,
+  {
+    self.trigger();
+
+    let mut drained = core::pin::pin!(self.inner.tasks.wait());
+    let mut grace = core::pin::pin!(grace);
+    let expired = core::future::poll_fn(|cx| {
+      if drained.as_mut().poll(cx).is_ready() {
+        return Poll::Ready(false);
+      }
+      grace.as_mut().poll(cx).map(|_| true)
+    })
+    .await;
+
+    if expired {
+      self.escalate()
+    } else {
+      Outcome::Drained
+    }
+  }
+
+  /// Blocking counterpart of [`drain`](Self::drain), with the grace period
+  /// given as a [`Duration`](std::time::Duration).
+  ///
+  /// Do not call it from inside an async task.
+  #[cfg(not(target_family = "wasm"))]
+  #[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+  pub fn drain_blocking(&self, grace: std::time::Duration) -> Outcome {
+    self.trigger();
+
+    if self.inner.tasks.wait_blocking_timeout(grace) {
+      Outcome::Drained
+    } else {
+      self.escalate()
+    }
+  }
+
+  /// Fires the trigger when the process receives `SIGINT` or `SIGTERM`.
+  ///
+  /// The signals are handled on a background thread. The first one fires
+  /// the trigger; a second one falls back to the signal's default action,
+  /// so pressing Ctrl-C twice still terminates a stuck process.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the signal handlers cannot be installed or the
+  /// thread cannot be spawned.
+  #[cfg(all(unix, feature = "signal"))]
+  #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "signal"))))]
+  pub fn trigger_on_signals(&self) -> std::io::Result<()> {
+    use signal_hook::{
+      consts::{SIGINT, SIGTERM},
+      iterator::Signals,
+      low_level::emulate_default_handler,
+    };
+
+    let mut signals = Signals::new([SIGINT, SIGTERM])?;
+    let this = self.clone();
+    std::thread::Builder::new()
+      .name("wg-shutdown-signals".into())
+      .spawn(move || {
+        let mut received = false;
+        for signal in signals.forever() {
+          if received {
+            let _ = emulate_default_handler(signal);
+          }
+          received = true;
+          this.trigger();
+        }
+      })?;
+    Ok(())
+  }
+
+  fn escalate(&self) -> Outcome {
+    let remaining = self.remaining();
+    if remaining == 0 {
+      // The last task finished just as the grace period expired.
+      return Outcome::Drained;
+    }
+
+    // Take the callback out first so it does not run under the lock.
+    let escalation = self.inner.escalation.lock_me().take();
+    if let Some(f) = escalation {
+      f();
+    }
+    Outcome::Escalated { remaining }
+  }
+}
+
+/// A registered task, returned by [`Shutdown::register`]. Dropping it
+/// reports the task as finished.
+pub struct ShutdownGuard {
+  inner: Arc<Inner>,
+}
+
+impl core::fmt::Debug for ShutdownGuard {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("ShutdownGuard")
+      .field("triggered", &self.is_triggered())
+      .finish()
+  }
+}
+
+impl ShutdownGuard {
+  /// Returns `true` once the shutdown has been triggered.
+  pub fn is_triggered(&self) -> bool {
+    self.inner.is_triggered()
+  }
+
+  /// Returns a future that resolves once the shutdown has been triggered.
+  pub fn triggered(&self) -> Triggered<'_> {
+    Triggered {
+      inner: &self.inner,
+      listener: None,
+    }
+  }
+}
+
+impl Drop for ShutdownGuard {
+  fn drop(&mut self) {
+    self.inner.tasks.done();
+  }
+}
+
+/// A future returned by [`Shutdown::triggered`] and
+/// [`ShutdownGuard::triggered`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Triggered<'a> {
+  inner: &'a Inner,
+  listener: Option<EventListener>,
+}
+
+impl core::fmt::Debug for Triggered<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Triggered")
+      .field("triggered", &self.inner.is_triggered())
+      .finish()
+  }
+}
+
+impl Future for Triggered<'_> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = self.get_mut();
+    loop {
+      if this.inner.is_triggered() {
+        this.listener = None;
+        return Poll::Ready(());
+      }
+
+      match &mut this.listener {
+        Some(l) => {
+          if Pin::new(l).poll(cx).is_pending() {
+            return Poll::Pending;
+          }
+          this.listener = None;
+        }
+        // Listen, then loop to re-check the flag, so a trigger between the
+        // check above and `listen` is not missed.
+        None => this.listener = Some(this.inner.event.listen()),
+      }
+    }
+  }
+}
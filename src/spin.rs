@@ -1,53 +1,277 @@
 //! A lock-free, atomic-counter WaitGroup.
 //!
-//! [`WaitGroup`] uses atomic operations and an adaptive spin-loop to wait
+//! `WaitGroup` uses atomic operations and an adaptive spin-loop to wait
 //! for completion, so it needs no `Mutex`/`Condvar`. It works under `std`
-//! and in `no_std + alloc` environments, but it is not usable in pure
-//! `core`/no-allocation environments because it stores shared state in `Arc`.
+//! and in `no_std + alloc` environments. It stores shared state in `Arc`,
+//! so it is not available in pure `core`/no-allocation environments; use
+//! [`WaitGroupRef`](crate::spin::WaitGroupRef) there instead, which
+//! borrows a caller-owned [`AtomicUsize`](crate::spin::AtomicUsize) (on the
+//! stack or in a `static`) and needs neither `std` nor `alloc`.
+//!
 //! On `std` the adaptive backoff yields the OS thread once its short-spin
-//! budget is exhausted; on `no_std + alloc` it keeps spinning.
+//! budget is exhausted; without `std` it keeps spinning. The behavior
+//! between two checks of the counter is pluggable: pass a
+//! [`Relax`](crate::spin::Relax) strategy to `wait_with` to spin only,
+//! sleep, or run a platform idle instruction such as `WFE`.
+//!
+//! `add` and `done` need atomic compare-and-swap. On targets without it
+//! (e.g. `thumbv6m`, RISC-V cores without the `A` extension), enable the
+//! `portable-atomic` feature — and one of `portable-atomic`'s
+//! `critical-section` / `unsafe-assume-single-core` options in the final
+//! binary — to route the counter through `portable_atomic::AtomicUsize`.
 //!
 //! Use `WaitGroup` when:
 //! - You are in a `no_std + alloc` environment.
 //! - The expected wait is short and you want to avoid OS synchronization
 //!   overhead.
 //!
-//! Prefer [`WaitGroup`](crate::WaitGroup) for longer waits under `std`.
-//! Prefer [`future::WaitGroup`](crate::future::WaitGroup) for async contexts.
+//! Use `WaitGroupRef` when there is no allocator at all.
+//!
+//! Prefer the blocking `wg::WaitGroup` for longer waits under `std`.
+//! Prefer `wg::future::WaitGroup` for async contexts.
 
-use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+use crate::primitives::Weak;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+use crate::primitives::{spin_yield, Ordering};
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::{primitives::Arc, seal::Seal, waiters::Waiters, Sealed, WaitOutcome};
 
-#[cfg(all(any(feature = "std", feature = "alloc"), not(feature = "triomphe")))]
-use std::sync::Arc;
+/// The counter type [`WaitGroupRef`] borrows.
+///
+/// This is [`core::sync::atomic::AtomicUsize`], or
+/// `portable_atomic::AtomicUsize` when the `portable-atomic` feature is
+/// enabled. Naming it through this re-export keeps code working with
+/// either.
+pub use crate::primitives::AtomicUsize;
 
-#[cfg(feature = "triomphe")]
-use triomphe::Arc;
+/// Number of exponentially growing spin rounds before a [`Relax`]
+/// strategy falls back to its slow path (`2^SPIN_LIMIT` spins per round
+/// at the cap).
+const SPIN_LIMIT: u32 = 6;
 
-/// Adaptive backoff: spins with exponentially increasing delay, then yields
-/// (on `std`) or continues spinning (on pure `no_std`).
+/// One round of exponential spinning. Returns `true` once the spin
+/// budget is exhausted and the caller should take its slow path instead.
 ///
 /// Inspired by `crossbeam_utils::Backoff` but inlined to avoid a dependency.
 #[inline]
-fn backoff_step(iter: &mut u32) {
-  const SPIN_LIMIT: u32 = 6;
-  if *iter <= SPIN_LIMIT {
-    for _ in 0..(1u32 << *iter) {
-      core::hint::spin_loop();
+pub(crate) fn spin_step(iter: &mut u32) -> bool {
+  if *iter > SPIN_LIMIT {
+    return true;
+  }
+  for _ in 0..(1u32 << *iter) {
+    core::hint::spin_loop();
+  }
+  *iter += 1;
+  false
+}
+
+/// What a spinning waiter does between two checks of the counter.
+///
+/// `WaitGroup::wait_with` and [`WaitGroupRef::wait_with`] call
+/// [`relax`](Relax::relax) once every time they observe a non-zero
+/// counter. A strategy is passed by value, so each wait starts from a
+/// fresh state.
+///
+/// The crate ships [`Spin`], and with `std` also `SpinThenYield` and
+/// `SpinThenSleep`. Any `FnMut()` closure is a strategy too, which is the
+/// easiest way to hook in a platform-specific idle instruction:
+///
+/// ```rust
+/// use wg::spin::WaitGroup;
+///
+/// let wg = WaitGroup::from(1);
+/// let t_wg = wg.clone();
+/// std::thread::spawn(move || t_wg.done());
+///
+/// // e.g. `cortex_m::asm::wfe` on an embedded target.
+/// wg.wait_with(|| std::thread::yield_now());
+/// ```
+pub trait Relax {
+  /// Called once per unsuccessful check of the counter.
+  fn relax(&mut self);
+}
+
+impl<F: FnMut()> Relax for F {
+  #[inline]
+  fn relax(&mut self) {
+    self()
+  }
+}
+
+/// Spin-only strategy: spins with exponentially increasing delay, then
+/// keeps spinning at the capped delay. Never yields to the OS.
+///
+/// This is what `WaitGroup::wait` uses without `std`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Spin {
+  iter: u32,
+}
+
+impl Spin {
+  /// Creates a new `Spin` strategy.
+  #[inline]
+  pub const fn new() -> Self {
+    Self { iter: 0 }
+  }
+}
+
+impl Relax for Spin {
+  #[inline]
+  fn relax(&mut self) {
+    if spin_step(&mut self.iter) {
+      for _ in 0..(1u32 << SPIN_LIMIT) {
+        core::hint::spin_loop();
+      }
     }
-  } else {
-    #[cfg(feature = "std")]
-    std::thread::yield_now();
-    #[cfg(not(feature = "std"))]
-    for _ in 0..(1u32 << SPIN_LIMIT) {
-      core::hint::spin_loop();
+  }
+}
+
+/// Spins with exponentially increasing delay, then yields the OS thread
+/// on every further round.
+///
+/// This is what [`WaitGroup::wait`] uses under `std`.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SpinThenYield {
+  iter: u32,
+}
+
+#[cfg(feature = "std")]
+impl SpinThenYield {
+  /// Creates a new `SpinThenYield` strategy.
+  #[inline]
+  pub const fn new() -> Self {
+    Self { iter: 0 }
+  }
+}
+
+#[cfg(feature = "std")]
+impl Relax for SpinThenYield {
+  #[inline]
+  fn relax(&mut self) {
+    if spin_step(&mut self.iter) {
+      std::thread::yield_now();
     }
   }
-  *iter = iter.saturating_add(1);
 }
 
-#[derive(Debug)]
+/// Spins with exponentially increasing delay, then puts the OS thread to
+/// sleep for a fixed duration on every further round.
+///
+/// Trades wake-up latency for CPU time; suited to waits that are expected
+/// to be long.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone, Copy)]
+pub struct SpinThenSleep {
+  iter: u32,
+  duration: std::time::Duration,
+}
+
+#[cfg(feature = "std")]
+impl SpinThenSleep {
+  /// Creates a strategy that sleeps for `duration` once the spin budget
+  /// is exhausted.
+  #[inline]
+  pub const fn new(duration: std::time::Duration) -> Self {
+    Self { iter: 0, duration }
+  }
+}
+
+#[cfg(feature = "std")]
+impl Relax for SpinThenSleep {
+  #[inline]
+  fn relax(&mut self) {
+    if spin_step(&mut self.iter) {
+      std::thread::sleep(self.duration);
+    }
+  }
+}
+
+/// The strategy used by `wait`: yields on `std`, spins otherwise.
+#[cfg(all(
+  feature = "std",
+  any(feature = "portable-atomic", target_has_atomic = "ptr")
+))]
+type DefaultRelax = SpinThenYield;
+#[cfg(all(
+  not(feature = "std"),
+  any(feature = "portable-atomic", target_has_atomic = "ptr")
+))]
+type DefaultRelax = Spin;
+
+// The counter operations below are shared by the `Arc`-backed
+// `WaitGroup` and the borrowed `WaitGroupRef`, so both variants have
+// exactly the same semantics. They need compare-and-swap, which targets
+// like `thumbv6m` only get through `portable-atomic`; without it, only
+// the `Relax` strategies (used by `cs::WaitGroup`) are built there.
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[inline]
+fn add(counter: &AtomicUsize, num: usize) {
+  // Use `fetch_update` + `checked_add` so overflow is caught in ALL
+  // builds, not just debug. A plain `fetch_add` would silently wrap
+  // in release mode, which could reset the counter to zero and let
+  // `wait()` return prematurely or hang.
+  counter
+    .fetch_update(Ordering::Release, Ordering::Relaxed, |prev| {
+      prev.checked_add(num)
+    })
+    .unwrap_or_else(|prev| panic!("WaitGroup counter overflow: prev={prev}, num={num}"));
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[inline]
+fn done(counter: &AtomicUsize, num: usize) -> usize {
+  // Saturate at zero; an already-zero counter is left untouched.
+  match counter.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+    (v != 0).then(|| v.saturating_sub(num))
+  }) {
+    Ok(old) => old.saturating_sub(num),
+    // Over-done: counter was already zero. Silently no-op.
+    Err(_) => 0,
+  }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[inline]
+fn wait_at_most<R: Relax>(counter: &AtomicUsize, n: usize, mut relax: R) {
+  // Fast path: counter already at or below the target.
+  if counter.load(Ordering::Acquire) <= n {
+    return;
+  }
+
+  while counter.load(Ordering::Acquire) > n {
+    relax.relax();
+    spin_yield();
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
 struct Inner {
   counter: AtomicUsize,
+  seal: Seal,
+  // Number of `reset` calls so far.
+  resets: AtomicUsize,
+  waiters: Waiters,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Inner {
+  fn new(count: usize) -> Self {
+    Self {
+      counter: AtomicUsize::new(count),
+      seal: Seal::new(),
+      resets: AtomicUsize::new(0),
+      waiters: Waiters::new(),
+    }
+  }
 }
 
 /// A lock-free WaitGroup that waits for a collection of tasks to finish.
@@ -79,30 +303,29 @@ struct Inner {
 /// wg.wait();
 /// assert_eq!(ctr.load(Ordering::Relaxed), 5);
 /// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
 pub struct WaitGroup {
   inner: Arc<Inner>,
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl Default for WaitGroup {
   fn default() -> Self {
-    Self {
-      inner: Arc::new(Inner {
-        counter: AtomicUsize::new(0),
-      }),
-    }
+    Self::from(0)
   }
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl From<usize> for WaitGroup {
   fn from(count: usize) -> Self {
     Self {
-      inner: Arc::new(Inner {
-        counter: AtomicUsize::new(count),
-      }),
+      inner: Arc::new(Inner::new(count)),
     }
   }
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl Clone for WaitGroup {
   fn clone(&self) -> Self {
     Self {
@@ -111,6 +334,7 @@ impl Clone for WaitGroup {
   }
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl core::fmt::Debug for WaitGroup {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     f.debug_struct("WaitGroup")
@@ -120,6 +344,8 @@ impl core::fmt::Debug for WaitGroup {
 }
 
 /// Shorthand for [`add`](WaitGroup::add), discarding the returned clone.
+/// Like `add`, it panics if the group has been
+/// [sealed](WaitGroup::seal).
 ///
 /// ```
 /// use wg::spin::WaitGroup;
@@ -127,12 +353,30 @@ impl core::fmt::Debug for WaitGroup {
 /// wg += 3;
 /// assert_eq!(wg.remaining(), 3);
 /// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl core::ops::AddAssign<usize> for WaitGroup {
   fn add_assign(&mut self, rhs: usize) {
     self.add(rhs);
   }
 }
 
+/// Shorthand for [`done_n`](WaitGroup::done_n), discarding the remaining
+/// count.
+///
+/// ```
+/// use wg::spin::WaitGroup;
+/// let mut wg = WaitGroup::from(3);
+/// wg -= 2;
+/// assert_eq!(wg.remaining(), 1);
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl core::ops::SubAssign<usize> for WaitGroup {
+  fn sub_assign(&mut self, rhs: usize) {
+    self.done_n(rhs);
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl WaitGroup {
   /// Creates a new `WaitGroup` with a counter of zero.
   pub fn new() -> Self {
@@ -184,26 +428,66 @@ impl WaitGroup {
   ///
   /// Calls that bring the counter up from zero must happen before any
   /// [`wait`](Self::wait) call — typically by running them on the main
-  /// thread before spawning the workers. Adding while another thread
-  /// is in `wait` is a race and not supported.
+  /// thread before spawning the workers. A plain `add` while another
+  /// thread is in `wait` is a race; work that discovers more work should
+  /// add it with [`Token::fork`] instead.
   ///
   /// If a `WaitGroup` is reused for several independent rounds, new
   /// `add` calls must happen after all previous [`wait`](Self::wait)
   /// calls have returned.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
   pub fn add(&self, num: usize) -> Self {
-    // Use `fetch_update` + `checked_add` so overflow is caught in ALL
-    // builds, not just debug. A plain `fetch_add` would silently wrap
-    // in release mode, which could reset the counter to zero and let
-    // `wait()` return prematurely or hang.
-    self
-      .inner
-      .counter
-      .fetch_update(Ordering::Release, Ordering::Relaxed, |prev| {
-        prev.checked_add(num)
-      })
-      .unwrap_or_else(|prev| panic!("WaitGroup counter overflow: prev={prev}, num={num}"));
-    Self {
-      inner: self.inner.clone(),
+    match self.try_add(num) {
+      Ok(wg) => wg,
+      Err(_) => panic!("add on a sealed WaitGroup"),
+    }
+  }
+
+  /// Like [`add`](Self::add), but returns `Err(Sealed)` instead of
+  /// adding once the group has been [sealed](Self::seal).
+  pub fn try_add(&self, num: usize) -> Result<Self, Sealed> {
+    self.inner.seal.precheck()?;
+    add(&self.inner.counter, num);
+    if let Err(sealed) = self.inner.seal.check() {
+      done(&self.inner.counter, num);
+      return Err(sealed);
+    }
+    Ok(self.clone())
+  }
+
+  /// Seals the group: from now on, [`try_add`](Self::try_add) fails and
+  /// [`add`](Self::add) panics, while the work already counted runs to
+  /// completion. See [`crate::WaitGroup::seal`].
+  pub fn seal(&self) {
+    self.inner.seal.seal();
+  }
+
+  /// Returns `true` once the group has been [sealed](Self::seal).
+  pub fn is_sealed(&self) -> bool {
+    self.inner.seal.is_sealed()
+  }
+
+  /// Forcibly sets the counter to zero, releasing every waiter. Those in
+  /// [`wait_outcome`](Self::wait_outcome) get [`WaitOutcome::Reset`].
+  /// See [`crate::WaitGroup::reset`].
+  pub fn reset(&self) {
+    // Published by the `Release` store below.
+    self.inner.resets.fetch_add(1, Ordering::Relaxed);
+    self.inner.counter.store(0, Ordering::Release);
+  }
+
+  /// Like [`wait`](Self::wait), but also reports whether the counter
+  /// drained or the group was [reset](Self::reset) in the meantime.
+  pub fn wait_outcome(&self) -> WaitOutcome {
+    let resets = self.inner.resets.load(Ordering::Acquire);
+    self.wait();
+    if self.inner.resets.load(Ordering::Acquire) == resets {
+      WaitOutcome::Drained
+    } else {
+      WaitOutcome::Reset
     }
   }
 
@@ -212,15 +496,18 @@ impl WaitGroup {
   /// If the counter is already zero, this call is a no-op and returns `0`.
   /// No panic is raised.
   pub fn done(&self) -> usize {
-    match self
-      .inner
-      .counter
-      .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| v.checked_sub(1))
-    {
-      Ok(old) => old - 1,
-      // Over-done: counter was already zero. Silently no-op.
-      Err(_) => 0,
-    }
+    done(&self.inner.counter, 1)
+  }
+
+  /// Decrements the counter by `num` and returns the remaining count —
+  /// the equivalent of Go's `Add(-n)`.
+  ///
+  /// This is the same as calling [`done`](Self::done) `num` times, but
+  /// it is a single atomic update. Underflow saturates: if `num` is
+  /// larger than the remaining count, the counter becomes zero. No panic
+  /// is raised.
+  pub fn done_n(&self, num: usize) -> usize {
+    done(&self.inner.counter, num)
   }
 
   /// Returns the current counter value — the number of tasks still
@@ -229,19 +516,536 @@ impl WaitGroup {
     self.inner.counter.load(Ordering::Acquire)
   }
 
+  /// Returns the number of threads currently spinning in one of the
+  /// `wait` methods on this group, through any of its handles, including
+  /// `WeakWaitGroup::wait`. Like [`remaining`](Self::remaining), it
+  /// is a snapshot.
+  pub fn waiters(&self) -> usize {
+    self.inner.waiters.count()
+  }
+
   /// Blocks (spinning with adaptive backoff) until the counter reaches zero.
   ///
   /// On `std`, the backoff yields the OS thread after a short spin phase.
-  /// On pure `no_std`, it continues spinning indefinitely.
+  /// On pure `no_std`, it continues spinning indefinitely. Use
+  /// [`wait_with`](Self::wait_with) to pick a different strategy.
   pub fn wait(&self) {
-    // Fast path: counter already zero.
-    if self.inner.counter.load(Ordering::Acquire) == 0 {
-      return;
+    let _waiting = self.inner.waiters.enter();
+    wait_at_most(&self.inner.counter, 0, DefaultRelax::new())
+  }
+
+  /// Blocks until the counter reaches zero, calling `relax` between
+  /// checks of the counter.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # #[cfg(feature = "std")] {
+  /// use std::time::Duration;
+  /// use wg::spin::{SpinThenSleep, WaitGroup};
+  ///
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  /// std::thread::spawn(move || {
+  ///     std::thread::sleep(Duration::from_millis(10));
+  ///     t_wg.done();
+  /// });
+  ///
+  /// wg.wait_with(SpinThenSleep::new(Duration::from_millis(1)));
+  /// # }
+  /// ```
+  pub fn wait_with<R: Relax>(&self, relax: R) {
+    let _waiting = self.inner.waiters.enter();
+    wait_at_most(&self.inner.counter, 0, relax)
+  }
+
+  /// Blocks (spinning with adaptive backoff) until at most `n` tasks
+  /// remain, i.e. until the counter is `<= n`.
+  ///
+  /// `wait_for_at_most(0)` is the same as [`wait`](Self::wait).
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::spin::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// for _ in 0..5 {
+  ///     let t_wg = wg.add(1);
+  ///     std::thread::spawn(move || t_wg.done());
+  /// }
+  ///
+  /// // Continue once 3 of the 5 workers are done.
+  /// wg.wait_for_at_most(2);
+  /// assert!(wg.remaining() <= 2);
+  /// ```
+  pub fn wait_for_at_most(&self, n: usize) {
+    let _waiting = self.inner.waiters.enter();
+    wait_at_most(&self.inner.counter, n, DefaultRelax::new())
+  }
+
+  /// Adds one unit to the counter and returns a [`Token`] for it.
+  ///
+  /// Unlike the handle returned by [`add`](Self::add), a token can only
+  /// complete its own units, once: [`Token::done`] consumes it. It cannot
+  /// wait, add, or complete anybody else's work.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::spin::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let token = wg.add_token();
+  /// std::thread::spawn(move || token.done());
+  /// wg.wait();
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn add_token(&self) -> Token {
+    self.add_tokens(1)
+  }
+
+  /// Adds `num` units to the counter and returns a single [`Token`]
+  /// holding all of them. Use [`Token::split`] to hand parts of it to
+  /// different workers.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn add_tokens(&self, num: usize) -> Token {
+    Token {
+      wg: self.add(num),
+      units: num,
     }
+  }
 
-    let mut iter = 0u32;
-    while self.inner.counter.load(Ordering::Acquire) != 0 {
-      backoff_step(&mut iter);
+  /// Creates a [`WeakWaitGroup`] observing this group without keeping it
+  /// alive.
+  ///
+  /// Only available when the selected `Arc` keeps a weak count, i.e. not
+  /// with the `triomphe` feature (unless `portable-atomic` overrides it).
+  #[cfg(all(
+    any(feature = "std", feature = "alloc"),
+    not(loom),
+    any(feature = "portable-atomic", not(feature = "triomphe"))
+  ))]
+  #[cfg_attr(docsrs, doc(cfg(not(feature = "triomphe"))))]
+  pub fn downgrade(&self) -> WeakWaitGroup {
+    WeakWaitGroup {
+      inner: Arc::downgrade(&self.inner),
     }
   }
 }
+
+/// A weak handle to a [`WaitGroup`], created by
+/// [`WaitGroup::downgrade`].
+///
+/// It can read and wait on the counter, but does not keep the group
+/// alive: once every `WaitGroup` handle is dropped, the shared state is
+/// freed, [`upgrade`](Self::upgrade) returns `None` and [`wait`](Self::wait)
+/// returns even if the counter never reached zero. See
+/// `wg::WeakWaitGroup` for the blocking variant.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::spin::WaitGroup;
+///
+/// let wg = WaitGroup::new();
+/// let t_wg = wg.add(1);
+/// let observer = wg.downgrade();
+/// assert_eq!(observer.remaining(), 1);
+///
+/// drop(wg);
+/// drop(t_wg); // gone without calling `done`
+/// observer.wait();
+/// assert_eq!(observer.remaining(), 0);
+/// assert!(observer.upgrade().is_none());
+/// ```
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+#[cfg_attr(docsrs, doc(cfg(not(feature = "triomphe"))))]
+pub struct WeakWaitGroup {
+  inner: Weak<Inner>,
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl Clone for WeakWaitGroup {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl core::fmt::Debug for WeakWaitGroup {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("WeakWaitGroup")
+      .field("counter", &self.remaining())
+      .field("orphaned", &(self.inner.strong_count() == 0))
+      .finish()
+  }
+}
+
+#[cfg(all(
+  any(feature = "std", feature = "alloc"),
+  not(loom),
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+impl WeakWaitGroup {
+  /// Returns a [`WaitGroup`] handle, or `None` if every handle has
+  /// already been dropped.
+  pub fn upgrade(&self) -> Option<WaitGroup> {
+    self.inner.upgrade().map(|inner| WaitGroup { inner })
+  }
+
+  /// Returns the current counter value, or `0` once every [`WaitGroup`]
+  /// handle has been dropped and the group is gone.
+  pub fn remaining(&self) -> usize {
+    self
+      .inner
+      .upgrade()
+      .map_or(0, |inner| inner.counter.load(Ordering::Acquire))
+  }
+
+  /// Blocks (spinning with adaptive backoff) until the counter reaches
+  /// zero, or until every [`WaitGroup`] handle has been dropped.
+  pub fn wait(&self) {
+    // Upgrade only for each check: holding the group across the backoff
+    // would keep it alive.
+    match self.inner.upgrade() {
+      Some(inner) => inner.waiters.arrive(),
+      None => return,
+    }
+    let mut relax = DefaultRelax::new();
+    loop {
+      let Some(inner) = self.inner.upgrade() else {
+        return;
+      };
+      if inner.counter.load(Ordering::Acquire) == 0 {
+        inner.waiters.leave();
+        return;
+      }
+      drop(inner);
+      relax.relax();
+      spin_yield();
+    }
+  }
+}
+
+/// A completion token for units of work added with
+/// [`WaitGroup::add_token`] or [`WaitGroup::add_tokens`].
+///
+/// [`done`](Self::done) consumes the token, it cannot wait on the group,
+/// and it only adds to it through [`fork`](Self::fork). Dropping it also
+/// completes its units. See [`crate::Token`] for details.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+#[must_use = "dropping a `Token` completes its units right away"]
+pub struct Token {
+  wg: WaitGroup,
+  units: usize,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Drop for Token {
+  fn drop(&mut self) {
+    if self.units != 0 {
+      self.wg.done_n(self.units);
+    }
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl core::fmt::Debug for Token {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Token").field("units", &self.units).finish()
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Token {
+  /// Returns the number of units this token completes.
+  pub fn units(&self) -> usize {
+    self.units
+  }
+
+  /// Completes every unit of this token at once and returns the
+  /// remaining count, like [`WaitGroup::done_n`].
+  pub fn done(mut self) -> usize {
+    let units = core::mem::take(&mut self.units);
+    self.wg.done_n(units)
+  }
+
+  /// Splits the token into one holding the first `at` units and one
+  /// holding the rest.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `at` is greater than [`units`](Self::units).
+  pub fn split(mut self, at: usize) -> (Token, Token) {
+    assert!(
+      at <= self.units,
+      "split point {at} out of range for a token of {} units",
+      self.units
+    );
+    let rest = Token {
+      wg: self.wg.clone(),
+      units: self.units - at,
+    };
+    self.units = at;
+    (self, rest)
+  }
+
+  /// Adds `num` units to the group for work discovered while this
+  /// token's own units are outstanding, and returns a token holding
+  /// them. Unlike [`WaitGroup::add`], this is safe while another thread is
+  /// in `wait`; see [`crate::Token::fork`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if this token holds no units, since nothing would then keep
+  /// the counter from reaching zero before the new units are added, or if
+  /// the group has been [sealed](WaitGroup::seal).
+  pub fn fork(&self, num: usize) -> Token {
+    assert!(self.units != 0, "cannot fork a token that holds no units");
+    self.wg.add_tokens(num)
+  }
+}
+
+/// A spinning WaitGroup where every handle is one participant, and
+/// dropping a handle counts as `done`.
+///
+/// Cloning adds a participant and dropping a clone removes it;
+/// [`wait`](Self::wait) consumes the caller's own handle and spins until
+/// every other clone has been dropped. See [`crate::AutoWaitGroup`] for
+/// the blocking variant.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::spin::AutoWaitGroup;
+///
+/// let wg = AutoWaitGroup::new();
+/// for _ in 0..5 {
+///     let wg = wg.clone();
+///     std::thread::spawn(move || {
+///         // do some work; dropping `wg` reports it finished
+///         drop(wg);
+///     });
+/// }
+///
+/// wg.wait();
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub struct AutoWaitGroup {
+  wg: WaitGroup,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Default for AutoWaitGroup {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Clone for AutoWaitGroup {
+  fn clone(&self) -> Self {
+    Self { wg: self.wg.add(1) }
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Drop for AutoWaitGroup {
+  fn drop(&mut self) {
+    self.wg.done();
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl core::fmt::Debug for AutoWaitGroup {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("AutoWaitGroup")
+      .field("participants", &self.wg.remaining())
+      .finish()
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl AutoWaitGroup {
+  /// Creates a new group with one participant: the returned handle.
+  pub fn new() -> Self {
+    Self {
+      wg: WaitGroup::from(1),
+    }
+  }
+
+  /// Returns the number of live handles, including this one.
+  pub fn participants(&self) -> usize {
+    self.wg.remaining()
+  }
+
+  /// Returns the number of threads spinning in [`wait`](Self::wait),
+  /// whose handles no longer count as participants.
+  pub fn waiters(&self) -> usize {
+    self.wg.waiters()
+  }
+
+  /// Drops this handle and spins until every other clone has been
+  /// dropped too.
+  pub fn wait(self) {
+    // A plain clone shares the counter without adding a participant.
+    let wg = self.wg.clone();
+    drop(self);
+    wg.wait();
+  }
+}
+
+/// A borrowed, allocation-free WaitGroup.
+///
+/// `WaitGroupRef` has the same semantics as `WaitGroup`, but instead
+/// of owning its counter through an `Arc` it borrows an [`AtomicUsize`]
+/// that the caller owns — typically a `static` or a value on the stack
+/// that outlives every worker. It is `Copy`, so handing a handle to a
+/// worker is free, and it is available with neither `std` nor `alloc`.
+///
+/// Since it borrows nothing but the counter, it has no `waiters()`: there
+/// is nowhere to keep the count of blocked threads.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::spin::{AtomicUsize, WaitGroupRef};
+///
+/// static COUNTER: AtomicUsize = AtomicUsize::new(0);
+///
+/// let wg = WaitGroupRef::new(&COUNTER);
+/// for _ in 0..5 {
+///     let t_wg = wg.add(1);
+///     std::thread::spawn(move || {
+///         // do some work
+///         t_wg.done();
+///     });
+/// }
+///
+/// wg.wait();
+/// assert_eq!(wg.remaining(), 0);
+/// ```
+///
+/// With scoped threads, the counter can live on the stack:
+///
+/// ```rust
+/// use wg::spin::{AtomicUsize, WaitGroupRef};
+///
+/// let counter = AtomicUsize::new(0);
+/// let wg = WaitGroupRef::new(&counter);
+/// std::thread::scope(|s| {
+///     for _ in 0..3 {
+///         let t_wg = wg.add(1);
+///         s.spawn(move || t_wg.done());
+///     }
+///     wg.wait();
+/// });
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[derive(Clone, Copy)]
+pub struct WaitGroupRef<'a> {
+  counter: &'a AtomicUsize,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl core::fmt::Debug for WaitGroupRef<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("WaitGroupRef")
+      .field("counter", self.counter)
+      .finish()
+  }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<'a> From<&'a AtomicUsize> for WaitGroupRef<'a> {
+  fn from(counter: &'a AtomicUsize) -> Self {
+    Self::new(counter)
+  }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<'a> WaitGroupRef<'a> {
+  /// Creates a `WaitGroupRef` that uses `counter` as its shared counter.
+  ///
+  /// The current value of `counter` is taken as the initial count, so
+  /// `AtomicUsize::new(n)` behaves like `WaitGroup::from(n)`.
+  #[inline]
+  pub const fn new(counter: &'a AtomicUsize) -> Self {
+    Self { counter }
+  }
+
+  /// Increments the counter by `num` and returns a copy of this handle.
+  ///
+  /// See `WaitGroup::add` for the ordering requirements, which apply
+  /// unchanged.
+  pub fn add(&self, num: usize) -> Self {
+    add(self.counter, num);
+    *self
+  }
+
+  /// Decrements the counter by one and returns the remaining count.
+  ///
+  /// If the counter is already zero, this call is a no-op and returns `0`.
+  /// No panic is raised.
+  pub fn done(&self) -> usize {
+    done(self.counter, 1)
+  }
+
+  /// Decrements the counter by `num` and returns the remaining count.
+  ///
+  /// See `WaitGroup::done_n` for the underflow rules, which apply
+  /// unchanged.
+  pub fn done_n(&self, num: usize) -> usize {
+    done(self.counter, num)
+  }
+
+  /// Returns the current counter value — the number of tasks still
+  /// waiting to complete.
+  pub fn remaining(&self) -> usize {
+    self.counter.load(Ordering::Acquire)
+  }
+
+  /// Blocks (spinning with adaptive backoff) until the counter reaches zero.
+  ///
+  /// On `std`, the backoff yields the OS thread after a short spin phase.
+  /// On pure `no_std`, it continues spinning indefinitely. Use
+  /// [`wait_with`](Self::wait_with) to pick a different strategy.
+  pub fn wait(&self) {
+    wait_at_most(self.counter, 0, DefaultRelax::new())
+  }
+
+  /// Blocks until the counter reaches zero, calling `relax` between
+  /// checks of the counter. See [`Relax`].
+  pub fn wait_with<R: Relax>(&self, relax: R) {
+    wait_at_most(self.counter, 0, relax)
+  }
+
+  /// Blocks (spinning with adaptive backoff) until at most `n` tasks
+  /// remain. See `WaitGroup::wait_for_at_most`.
+  pub fn wait_for_at_most(&self, n: usize) {
+    wait_at_most(self.counter, n, DefaultRelax::new())
+  }
+}
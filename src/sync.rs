@@ -1,51 +1,55 @@
-trait Mu {
-  type Guard<'a>
-  where
-    Self: 'a;
-  fn lock_me(&self) -> Self::Guard<'_>;
-}
+// The counter and the wait/wake mechanism live in a backend module; the
+// `WaitGroup` handle below is the same for every backend. Loom cannot
+// model futex syscalls, so `--cfg loom` always uses the mutex backend.
+#[cfg(all(
+  feature = "futex",
+  not(loom),
+  any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "watchos",
+    windows
+  )
+))]
+#[path = "sync/futex.rs"]
+mod imp;
+#[cfg(not(all(
+  feature = "futex",
+  not(loom),
+  any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "watchos",
+    windows
+  )
+)))]
+#[path = "sync/mutex.rs"]
+mod imp;
 
-#[cfg(feature = "parking_lot")]
-impl<T: ?Sized> Mu for parking_lot::Mutex<T> {
-  type Guard<'a>
-    = parking_lot::MutexGuard<'a, T>
-  where
-    Self: 'a;
+mod panicked;
+mod phaser;
+mod watch;
 
-  fn lock_me(&self) -> Self::Guard<'_> {
-    self.lock()
-  }
-}
+pub use panicked::WorkerPanicked;
+pub use phaser::Phaser;
 
-#[cfg(not(feature = "parking_lot"))]
-impl<T: ?Sized> Mu for std::sync::Mutex<T> {
-  type Guard<'a>
-    = std::sync::MutexGuard<'a, T>
-  where
-    Self: 'a;
+use std::vec::Vec;
 
-  fn lock_me(&self) -> Self::Guard<'_> {
-    // Poisoning is not meaningful for a `usize` counter: the worst a
-    // panicking thread can leave behind is a stale count, not corrupt
-    // memory. Recovering the guard avoids cascading panics across all
-    // other threads that touch this WaitGroup.
-    self.lock().unwrap_or_else(|e| e.into_inner())
-  }
-}
+use imp::Inner;
 
-#[cfg(feature = "parking_lot")]
-use parking_lot::{Condvar, Mutex};
-#[cfg(not(feature = "triomphe"))]
-use std::sync::Arc;
-#[cfg(not(feature = "parking_lot"))]
-use std::sync::{Condvar, Mutex};
-#[cfg(feature = "triomphe")]
-use triomphe::Arc;
-
-struct Inner {
-  cvar: Condvar,
-  count: Mutex<usize>,
-}
+#[cfg(all(not(loom), any(feature = "portable-atomic", not(feature = "triomphe"))))]
+use crate::primitives::Weak;
+use crate::{
+  primitives::{thread, Arc},
+  spin::{Relax, Spin},
+  Sealed, WaitOutcome,
+};
 
 /// A WaitGroup waits for a collection of threads to finish.
 ///
@@ -56,6 +60,13 @@ struct Inner {
 ///
 /// A WaitGroup must not be copied after first use.
 ///
+/// By default the counter lives behind a `Mutex` and waiters sleep on a
+/// `Condvar`. With the `futex` feature on Linux, Android, FreeBSD, macOS,
+/// iOS, watchOS and Windows, the counter is a single atomic instead:
+/// `add`, `done` and `remaining` are lock-free and waiters sleep on a
+/// futex-style wait/wake that `done` only triggers on the transition to
+/// zero. Other targets keep the `Mutex` backend.
+///
 /// # Example
 ///
 /// ```rust
@@ -89,26 +100,26 @@ struct Inner {
 /// [`add`]: struct.WaitGroup.html#method.add
 pub struct WaitGroup {
   inner: Arc<Inner>,
+  spin_rounds: u32,
 }
 
+/// Rounds of spinning [`WaitGroup::wait`] performs before parking, unless
+/// configured otherwise with [`WaitGroup::set_spin_rounds`]. The first
+/// rounds spin for an exponentially increasing number of iterations, the
+/// same schedule as [`spin::Spin`](crate::spin::Spin).
+const DEFAULT_SPIN_ROUNDS: u32 = 7;
+
 impl Default for WaitGroup {
   fn default() -> Self {
-    Self {
-      inner: Arc::new(Inner {
-        cvar: Condvar::new(),
-        count: Mutex::new(0),
-      }),
-    }
+    Self::from(0)
   }
 }
 
 impl From<usize> for WaitGroup {
   fn from(count: usize) -> Self {
     Self {
-      inner: Arc::new(Inner {
-        cvar: Condvar::new(),
-        count: Mutex::new(count),
-      }),
+      inner: Arc::new(Inner::new(count)),
+      spin_rounds: DEFAULT_SPIN_ROUNDS,
     }
   }
 }
@@ -117,18 +128,22 @@ impl Clone for WaitGroup {
   fn clone(&self) -> Self {
     Self {
       inner: self.inner.clone(),
+      spin_rounds: self.spin_rounds,
     }
   }
 }
 
 impl std::fmt::Debug for WaitGroup {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let count = self.inner.count.lock_me();
-    f.debug_struct("WaitGroup").field("count", &*count).finish()
+    f.debug_struct("WaitGroup")
+      .field("count", &self.inner.remaining())
+      .finish()
   }
 }
 
 /// Shorthand for [`add`](WaitGroup::add), discarding the returned clone.
+/// Like `add`, it panics if the group has been
+/// [sealed](WaitGroup::seal).
 ///
 /// ```
 /// use wg::WaitGroup;
@@ -142,6 +157,21 @@ impl core::ops::AddAssign<usize> for WaitGroup {
   }
 }
 
+/// Shorthand for [`done_n`](WaitGroup::done_n), discarding the remaining
+/// count.
+///
+/// ```
+/// use wg::WaitGroup;
+/// let mut wg = WaitGroup::from(3);
+/// wg -= 2;
+/// assert_eq!(wg.remaining(), 1);
+/// ```
+impl core::ops::SubAssign<usize> for WaitGroup {
+  fn sub_assign(&mut self, rhs: usize) {
+    self.done_n(rhs);
+  }
+}
+
 impl WaitGroup {
   /// Creates a new wait group and returns the single reference to it.
   ///
@@ -201,22 +231,47 @@ impl WaitGroup {
   ///
   /// Calls that bring the counter up from zero must happen before any
   /// [`wait`](Self::wait) call — typically by running them on the main
-  /// thread before spawning the workers.
+  /// thread before spawning the workers. Work that discovers more work
+  /// while a thread waits should add it with [`Token::fork`] instead.
   ///
   /// If a `WaitGroup` is reused for several independent rounds, new
   /// `add` calls must happen after all previous [`wait`](Self::wait)
   /// calls have returned.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal). Use
+  /// [`try_add`](Self::try_add) where work can still arrive during
+  /// shutdown.
   pub fn add(&self, num: usize) -> Self {
-    let mut ctr = self.inner.count.lock_me();
-    // `checked_add` in all builds — not just debug. A wrap from
-    // usize::MAX + 1 → 0 would reset the counter and let `wait()`
-    // return prematurely. `+=` only panics on overflow in debug mode.
-    *ctr = ctr.checked_add(num).expect("WaitGroup counter overflow");
-    Self {
-      inner: self.inner.clone(),
+    match self.try_add(num) {
+      Ok(wg) => wg,
+      Err(_) => panic!("add on a sealed WaitGroup"),
     }
   }
 
+  /// Like [`add`](Self::add), but returns `Err(Sealed)` instead of
+  /// adding once the group has been [sealed](Self::seal).
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::{Sealed, WaitGroup};
+  ///
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.try_add(1).unwrap();
+  /// wg.seal();
+  /// assert_eq!(wg.try_add(1).unwrap_err(), Sealed);
+  ///
+  /// // The work counted before the seal still completes as usual.
+  /// std::thread::spawn(move || t_wg.done());
+  /// wg.wait();
+  /// ```
+  pub fn try_add(&self, num: usize) -> Result<Self, Sealed> {
+    self.inner.add(num)?;
+    Ok(self.clone())
+  }
+
   /// Decrements the WaitGroup counter by one, returning the remaining count.
   ///
   /// # Example
@@ -235,27 +290,85 @@ impl WaitGroup {
   ///
   /// ```
   pub fn done(&self) -> usize {
-    let mut val = self.inner.count.lock_me();
+    self.inner.done(1)
+  }
 
-    *val = if val.eq(&1) {
-      self.inner.cvar.notify_all();
-      0
-    } else if val.eq(&0) {
-      0
-    } else {
-      *val - 1
-    };
-    *val
+  /// Decrements the counter by `num` and returns the remaining count —
+  /// the equivalent of Go's `Add(-n)`.
+  ///
+  /// This is the same as calling [`done`](Self::done) `num` times, but
+  /// the counter is updated in a single step and waiters are woken at
+  /// most once, when it reaches zero.
+  ///
+  /// Underflow saturates: if `num` is larger than the remaining count,
+  /// the counter becomes zero, just as `done` on a zero counter is a
+  /// no-op. No panic is raised.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(100);
+  /// std::thread::spawn(move || {
+  ///     // process 100 items, then report them all at once
+  ///     t_wg.done_n(100);
+  /// });
+  /// wg.wait();
+  /// assert_eq!(wg.done_n(5), 0);
+  /// ```
+  pub fn done_n(&self, num: usize) -> usize {
+    self.inner.done(num)
   }
 
   /// Returns the current counter value — the number of threads still
   /// waiting to complete.
   pub fn remaining(&self) -> usize {
-    *self.inner.count.lock_me()
+    self.inner.remaining()
+  }
+
+  /// Returns the number of threads currently blocked on this group,
+  /// through any of its handles: in [`wait`](Self::wait),
+  /// [`wait_for_at_most`](Self::wait_for_at_most),
+  /// [`wait_outcome`](Self::wait_outcome), [`join`](Self::join) and
+  /// `WeakWaitGroup::wait`, and in [`wait_all`](crate::wait_all) or
+  /// [`wait_any`](crate::wait_any) with this group among theirs.
+  /// `wait_all` counts on one group at a time, the one it is blocked on,
+  /// while `wait_any` counts on every group.
+  ///
+  /// The value is a snapshot: use it for diagnostics, or to skip work
+  /// that only matters to a waiter, not for synchronization.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  /// let waiter = {
+  ///     let wg = wg.clone();
+  ///     std::thread::spawn(move || wg.wait())
+  /// };
+  ///
+  /// while wg.waiters() == 0 {
+  ///     std::thread::yield_now();
+  /// }
+  /// t_wg.done();
+  /// waiter.join().unwrap();
+  /// assert_eq!(wg.waiters(), 0);
+  /// ```
+  pub fn waiters(&self) -> usize {
+    self.inner.waiters().count()
   }
 
   /// wait blocks until the WaitGroup counter is zero.
   ///
+  /// The calling thread first spins for a short, bounded number of rounds
+  /// (see [`set_spin_rounds`](Self::set_spin_rounds)) and only parks if
+  /// the counter is still non-zero afterwards.
+  ///
   /// # Example
   ///
   /// ```rust
@@ -274,22 +387,725 @@ impl WaitGroup {
   /// wg.wait();
   /// ```
   pub fn wait(&self) {
-    let mut ctr = self.inner.count.lock_me();
+    let _waiting = self.inner.waiters().enter();
+    // Most waits end within microseconds of being started. Spin briefly
+    // before parking, so those waits don't pay for a syscall and a
+    // context switch.
+    let mut relax = Spin::new();
+    for _ in 0..self.spin_rounds {
+      if self.inner.try_remaining() == Some(0) {
+        return;
+      }
+      relax.relax();
+    }
 
-    if ctr.eq(&0) {
-      return;
+    self.inner.wait()
+  }
+
+  /// Blocks until at most `n` tasks remain, i.e. until the counter is
+  /// `<= n`. `wait_for_at_most(0)` is the same as [`wait`](Self::wait).
+  ///
+  /// Useful for quorums and pipelines that can continue once enough
+  /// workers have finished. Threshold waiters are woken separately from
+  /// plain waiters, and only by the `done` that brings the counter down
+  /// to the largest threshold currently waited for — not on every
+  /// decrement.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// for _ in 0..5 {
+  ///     let t_wg = wg.add(1);
+  ///     std::thread::spawn(move || {
+  ///         // query one replica
+  ///         t_wg.done();
+  ///     });
+  /// }
+  ///
+  /// // Quorum: continue once 3 of the 5 replicas have answered.
+  /// wg.wait_for_at_most(2);
+  /// assert!(wg.remaining() <= 2);
+  /// ```
+  pub fn wait_for_at_most(&self, n: usize) {
+    if n == 0 {
+      return self.wait();
     }
 
-    while *ctr > 0 {
-      #[cfg(feature = "parking_lot")]
-      {
-        self.inner.cvar.wait(&mut ctr);
+    let _waiting = self.inner.waiters().enter();
+    let mut relax = Spin::new();
+    for _ in 0..self.spin_rounds {
+      if matches!(self.inner.try_remaining(), Some(r) if r <= n) {
+        return;
       }
+      relax.relax();
+    }
+
+    self.inner.wait_at_most(n)
+  }
 
-      #[cfg(not(feature = "parking_lot"))]
-      {
-        ctr = self.inner.cvar.wait(ctr).unwrap_or_else(|e| e.into_inner());
+  /// Seals the group, so late work cannot extend a drain: from now on,
+  /// [`try_add`](Self::try_add) fails and [`add`](Self::add) panics. The
+  /// work already counted runs to completion, and waiters are released
+  /// once it has, as usual.
+  ///
+  /// An `add` racing with `seal` either fails or is counted before
+  /// `seal` returns, so a [`wait`](Self::wait) after `seal` covers it.
+  /// A group stays sealed, even across a [`reset`](Self::reset).
+  pub fn seal(&self) {
+    self.inner.seal();
+  }
+
+  /// Returns `true` once the group has been [sealed](Self::seal).
+  pub fn is_sealed(&self) -> bool {
+    self.inner.is_sealed()
+  }
+
+  /// Forcibly sets the counter to zero and releases every waiter. Those
+  /// in [`wait_outcome`](Self::wait_outcome) get [`WaitOutcome::Reset`].
+  ///
+  /// Use it to abandon a drain, e.g. once a shutdown grace period has
+  /// run out. Work that is still running when the group is reset keeps
+  /// its units: its later `done` calls decrement the counter again, so
+  /// only reuse the group once that work has finished.
+  pub fn reset(&self) {
+    self.inner.reset();
+  }
+
+  /// Like [`wait`](Self::wait), but also reports whether the counter
+  /// drained or the group was [reset](Self::reset) in the meantime.
+  ///
+  /// A reset racing with the last `done`, or with the start of the
+  /// call, can be reported either way.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::{WaitGroup, WaitOutcome};
+  ///
+  /// let wg = WaitGroup::new();
+  /// wg.add(1);
+  /// let t_wg = wg.clone();
+  /// std::thread::spawn(move || {
+  ///     // the worker is stuck; give up on it
+  ///     std::thread::sleep(std::time::Duration::from_millis(50));
+  ///     t_wg.reset();
+  /// });
+  /// assert_eq!(wg.wait_outcome(), WaitOutcome::Reset);
+  /// ```
+  pub fn wait_outcome(&self) -> WaitOutcome {
+    let resets = self.inner.resets();
+    self.wait();
+    if self.inner.resets() == resets {
+      WaitOutcome::Drained
+    } else {
+      WaitOutcome::Reset
+    }
+  }
+
+  /// Runs `f` as one unit of tracked work, then calls [`done`](Self::done),
+  /// even if `f` panics.
+  ///
+  /// A panic does not unwind past this call. Its payload is recorded on
+  /// the group instead, for [`join`](Self::join) to report once the
+  /// counter drains, and `None` is returned. Only the first payload is
+  /// kept until it is reported; later ones are dropped.
+  ///
+  /// Without `run`, a worker that panics never calls `done`, and the
+  /// waiter blocks forever. The group stays usable after a panic: the
+  /// payload lives behind a lock that recovers from poisoning, like the
+  /// counter's.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  /// std::thread::spawn(move || {
+  ///     t_wg.run(|| panic!("disk full"));
+  /// });
+  ///
+  /// let err = wg.join().unwrap_err();
+  /// assert_eq!(err.message(), Some("disk full"));
+  /// ```
+  pub fn run<F, R>(self, f: F) -> Option<R>
+  where
+    F: FnOnce() -> R,
+  {
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    let out = match result {
+      Ok(out) => Some(out),
+      Err(payload) => {
+        // Record before `done`, so a `join` released by it sees the
+        // payload.
+        self.inner.panic_slot().record(payload);
+        None
       }
+    };
+    self.done();
+    out
+  }
+
+  /// Like [`wait`](Self::wait), but returns `Err(WorkerPanicked)` if work
+  /// run through [`run`](Self::run) panicked.
+  ///
+  /// The error hands over the recorded payload, so only one `join` call
+  /// reports a given panic; call [`WorkerPanicked::resume`] on it to
+  /// continue unwinding on this thread instead.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// for i in 0..4 {
+  ///     let t_wg = wg.add(1);
+  ///     std::thread::spawn(move || {
+  ///         t_wg.run(|| assert!(i < 4));
+  ///     });
+  /// }
+  ///
+  /// // Propagate a worker panic, if any, to the waiting thread.
+  /// wg.join().unwrap_or_else(|e| e.resume());
+  /// ```
+  pub fn join(&self) -> Result<(), WorkerPanicked> {
+    self.wait();
+    match self.inner.panic_slot().take() {
+      Some(payload) => Err(WorkerPanicked::new(payload)),
+      None => Ok(()),
+    }
+  }
+
+  /// Adds one unit to the counter and returns a [`Token`] for it.
+  ///
+  /// Unlike the handle returned by [`add`](Self::add), a token can only
+  /// complete its own units, once: [`Token::done`] consumes it. It cannot
+  /// wait, add, or complete anybody else's work.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let token = wg.add_token();
+  /// std::thread::spawn(move || {
+  ///     // do some work
+  ///     token.done();
+  ///     // token.done(); // error: use of moved value
+  /// });
+  /// wg.wait();
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn add_token(&self) -> Token {
+    self.add_tokens(1)
+  }
+
+  /// Adds `num` units to the counter and returns a single [`Token`]
+  /// holding all of them. Use [`Token::split`] to hand parts of it to
+  /// different workers.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the group has been [sealed](Self::seal).
+  pub fn add_tokens(&self, num: usize) -> Token {
+    Token {
+      wg: self.add(num),
+      units: num,
+    }
+  }
+
+  /// Creates a [`WeakWaitGroup`] observing this group without keeping it
+  /// alive.
+  ///
+  /// Only available when the selected `Arc` keeps a weak count, i.e. not
+  /// with the `triomphe` feature (unless `portable-atomic` overrides it).
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let wg = WaitGroup::new();
+  /// let t_wg = wg.add(1);
+  /// let observer = wg.downgrade();
+  /// assert_eq!(observer.remaining(), 1);
+  ///
+  /// drop(wg);
+  /// let worker = std::thread::spawn(move || t_wg.done());
+  /// observer.wait();
+  /// assert_eq!(observer.remaining(), 0);
+  ///
+  /// // Once the worker's handle is gone too, the group is freed.
+  /// worker.join().unwrap();
+  /// assert!(observer.upgrade().is_none());
+  /// ```
+  #[cfg(all(not(loom), any(feature = "portable-atomic", not(feature = "triomphe"))))]
+  #[cfg_attr(docsrs, doc(cfg(not(feature = "triomphe"))))]
+  pub fn downgrade(&self) -> WeakWaitGroup {
+    WeakWaitGroup {
+      inner: Arc::downgrade(&self.inner),
+      spin_rounds: self.spin_rounds,
     }
   }
+
+  /// Returns how many rounds of spinning [`wait`](Self::wait) performs on
+  /// this handle before it parks the thread.
+  #[inline]
+  pub const fn spin_rounds(&self) -> u32 {
+    self.spin_rounds
+  }
+
+  /// Sets how many rounds of spinning [`wait`](Self::wait) performs on
+  /// this handle before it parks the thread. `0` disables the spin phase,
+  /// so `wait` parks right away.
+  ///
+  /// Spin round `i` busy-waits for `2^min(i, 6)` iterations, so the
+  /// default of 7 rounds costs roughly a hundred spin-loop hints. Raise
+  /// it when waits are known to be very short and a core can be spared,
+  /// lower it when waiters are numerous or CPU-bound.
+  ///
+  /// The setting is per handle: clones and handles returned by
+  /// [`add`](Self::add) start out with the value of the handle they came
+  /// from.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use wg::WaitGroup;
+  ///
+  /// let mut wg = WaitGroup::new();
+  /// wg.set_spin_rounds(0); // always park immediately
+  /// let t_wg = wg.add(1);
+  /// assert_eq!(t_wg.spin_rounds(), 0);
+  /// std::thread::spawn(move || t_wg.done());
+  /// wg.wait();
+  /// ```
+  #[inline]
+  pub fn set_spin_rounds(&mut self, rounds: u32) {
+    self.spin_rounds = rounds;
+  }
+
+  /// Builder-style variant of [`set_spin_rounds`](Self::set_spin_rounds).
+  #[inline]
+  pub fn with_spin_rounds(mut self, rounds: u32) -> Self {
+    self.spin_rounds = rounds;
+    self
+  }
+}
+
+/// A weak handle to a [`WaitGroup`], created by
+/// [`WaitGroup::downgrade`].
+///
+/// It can read and wait on the counter, but does not keep the group
+/// alive: once every `WaitGroup` handle is dropped, the shared state is
+/// freed, [`upgrade`](Self::upgrade) returns `None` and [`wait`](Self::wait)
+/// returns even if the counter never reached zero, since nothing is left
+/// to call `done`. Use it for monitoring code that must not keep a
+/// finished group around.
+///
+/// It holds a [`std::sync::Weak`], so it needs an `Arc` with a weak
+/// count: it is not available with the `triomphe` feature, unless
+/// `portable-atomic` selects its own `Arc` instead.
+#[cfg(all(not(loom), any(feature = "portable-atomic", not(feature = "triomphe"))))]
+#[cfg_attr(docsrs, doc(cfg(not(feature = "triomphe"))))]
+pub struct WeakWaitGroup {
+  inner: Weak<Inner>,
+  spin_rounds: u32,
+}
+
+#[cfg(all(not(loom), any(feature = "portable-atomic", not(feature = "triomphe"))))]
+impl Clone for WeakWaitGroup {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+      spin_rounds: self.spin_rounds,
+    }
+  }
+}
+
+#[cfg(all(not(loom), any(feature = "portable-atomic", not(feature = "triomphe"))))]
+impl std::fmt::Debug for WeakWaitGroup {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("WeakWaitGroup")
+      .field("count", &self.remaining())
+      .field("orphaned", &(self.inner.strong_count() == 0))
+      .finish()
+  }
+}
+
+#[cfg(all(not(loom), any(feature = "portable-atomic", not(feature = "triomphe"))))]
+impl WeakWaitGroup {
+  /// Returns a [`WaitGroup`] handle, or `None` if every handle has
+  /// already been dropped.
+  pub fn upgrade(&self) -> Option<WaitGroup> {
+    self.inner.upgrade().map(|inner| WaitGroup {
+      inner,
+      spin_rounds: self.spin_rounds,
+    })
+  }
+
+  /// Returns the current counter value, or `0` once every [`WaitGroup`]
+  /// handle has been dropped and the group is gone.
+  pub fn remaining(&self) -> usize {
+    self.inner.upgrade().map_or(0, |inner| inner.remaining())
+  }
+
+  /// Blocks until the counter reaches zero, or until every [`WaitGroup`]
+  /// handle has been dropped, whichever comes first.
+  pub fn wait(&self) {
+    let Some(inner) = self.inner.upgrade() else {
+      return;
+    };
+    // Park instead of sleeping on the group's condvar or futex, which
+    // would keep it alive. As in `wait_any`, register first, then check:
+    // the `done` that drains the group and the drop of its last handle
+    // both unpark us.
+    let me = thread::current();
+    inner.waiters().arrive();
+    inner.watchers().register(me.clone());
+    drop(inner);
+    loop {
+      let Some(inner) = self.inner.upgrade() else {
+        return;
+      };
+      if inner.drained() {
+        inner.watchers().unregister(me.id());
+        inner.waiters().leave();
+        return;
+      }
+      drop(inner);
+      thread::park();
+    }
+  }
+}
+
+/// A completion token for units of work added with
+/// [`WaitGroup::add_token`] or [`WaitGroup::add_tokens`].
+///
+/// A token can only complete its own units: [`done`](Self::done) consumes
+/// it, so completing the same work twice is a compile error, and it
+/// offers no way to wait on the group. It can only add to the group
+/// through [`fork`](Self::fork), which is safe while another thread
+/// waits.
+///
+/// Dropping a token without calling `done` completes its units as well,
+/// so a worker that returns early or panics cannot leave the waiter
+/// blocked.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::WaitGroup;
+///
+/// let wg = WaitGroup::new();
+/// let (a, b) = wg.add_tokens(3).split(1);
+/// assert_eq!((a.units(), b.units()), (1, 2));
+///
+/// std::thread::spawn(move || a.done());
+/// std::thread::spawn(move || b.done());
+/// wg.wait();
+/// ```
+///
+/// Completing a token twice does not compile:
+///
+/// ```rust,compile_fail
+/// let wg = wg::WaitGroup::new();
+/// let token = wg.add_token();
+/// token.done();
+/// token.done();
+/// ```
+#[must_use = "dropping a `Token` completes its units right away"]
+pub struct Token {
+  wg: WaitGroup,
+  units: usize,
+}
+
+impl Drop for Token {
+  fn drop(&mut self) {
+    if self.units != 0 {
+      self.wg.done_n(self.units);
+    }
+  }
+}
+
+impl std::fmt::Debug for Token {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Token").field("units", &self.units).finish()
+  }
+}
+
+impl Token {
+  /// Returns the number of units this token completes.
+  pub fn units(&self) -> usize {
+    self.units
+  }
+
+  /// Completes every unit of this token at once and returns the
+  /// remaining count, like [`WaitGroup::done_n`].
+  pub fn done(mut self) -> usize {
+    let units = core::mem::take(&mut self.units);
+    self.wg.done_n(units)
+  }
+
+  /// Splits the token into one holding the first `at` units and one
+  /// holding the rest.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `at` is greater than [`units`](Self::units).
+  pub fn split(mut self, at: usize) -> (Token, Token) {
+    assert!(
+      at <= self.units,
+      "split point {at} out of range for a token of {} units",
+      self.units
+    );
+    let rest = Token {
+      wg: self.wg.clone(),
+      units: self.units - at,
+    };
+    self.units = at;
+    (self, rest)
+  }
+
+  /// Adds `num` units to the group for work discovered while this
+  /// token's own units are outstanding, and returns a token holding
+  /// them.
+  ///
+  /// Unlike [`WaitGroup::add`], this is safe while another thread is in
+  /// [`wait`](WaitGroup::wait): this token keeps the counter above zero
+  /// until it completes, and the new units are counted before that, so
+  /// `wait` cannot return in between. A task tree where every task
+  /// forks the token of each task it spawns is waited on as a whole,
+  /// however it grows.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use std::sync::atomic::{AtomicUsize, Ordering};
+  /// use std::sync::Arc;
+  /// use wg::{Token, WaitGroup};
+  ///
+  /// fn visit(depth: u32, token: Token, seen: Arc<AtomicUsize>) {
+  ///     seen.fetch_add(1, Ordering::Relaxed);
+  ///     if depth > 0 {
+  ///         for _ in 0..2 {
+  ///             let child = token.fork(1);
+  ///             let seen = seen.clone();
+  ///             std::thread::spawn(move || visit(depth - 1, child, seen));
+  ///         }
+  ///     }
+  ///     token.done();
+  /// }
+  ///
+  /// let wg = WaitGroup::new();
+  /// let seen = Arc::new(AtomicUsize::new(0));
+  /// let root = wg.add_token();
+  /// let s = seen.clone();
+  /// std::thread::spawn(move || visit(3, root, s));
+  ///
+  /// wg.wait();
+  /// assert_eq!(seen.load(Ordering::Relaxed), 15);
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if this token holds no units, since nothing would then keep
+  /// the counter from reaching zero before the new units are added, or if
+  /// the group has been [sealed](WaitGroup::seal).
+  pub fn fork(&self, num: usize) -> Token {
+    assert!(self.units != 0, "cannot fork a token that holds no units");
+    self.wg.add_tokens(num)
+  }
+}
+
+/// A WaitGroup where every handle is one participant, and dropping a
+/// handle counts as `done`.
+///
+/// This is the model of crossbeam's `WaitGroup`: cloning adds a
+/// participant, dropping a clone removes it, and [`wait`](Self::wait)
+/// consumes the caller's own handle and blocks until every other clone
+/// has been dropped. Forgetting to call `done` is impossible, including
+/// when a participant panics and unwinds.
+///
+/// It is built on [`WaitGroup`], and has the same wake-up behavior.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use wg::AutoWaitGroup;
+///
+/// let wg = AutoWaitGroup::new();
+/// let ctr = Arc::new(AtomicUsize::new(0));
+///
+/// for _ in 0..5 {
+///     let wg = wg.clone();
+///     let ctr = ctr.clone();
+///     std::thread::spawn(move || {
+///         ctr.fetch_add(1, Ordering::Relaxed);
+///         drop(wg);
+///     });
+/// }
+///
+/// wg.wait();
+/// assert_eq!(ctr.load(Ordering::Relaxed), 5);
+/// ```
+pub struct AutoWaitGroup {
+  wg: WaitGroup,
+}
+
+impl Default for AutoWaitGroup {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Clone for AutoWaitGroup {
+  fn clone(&self) -> Self {
+    Self { wg: self.wg.add(1) }
+  }
+}
+
+impl Drop for AutoWaitGroup {
+  fn drop(&mut self) {
+    self.wg.done();
+  }
+}
+
+impl std::fmt::Debug for AutoWaitGroup {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("AutoWaitGroup")
+      .field("participants", &self.wg.remaining())
+      .finish()
+  }
+}
+
+impl AutoWaitGroup {
+  /// Creates a new group with one participant: the returned handle.
+  pub fn new() -> Self {
+    Self {
+      wg: WaitGroup::from(1),
+    }
+  }
+
+  /// Returns the number of live handles, including this one.
+  pub fn participants(&self) -> usize {
+    self.wg.remaining()
+  }
+
+  /// Returns the number of threads blocked in [`wait`](Self::wait),
+  /// whose handles no longer count as participants.
+  pub fn waiters(&self) -> usize {
+    self.wg.waiters()
+  }
+
+  /// Drops this handle and blocks until every other clone has been
+  /// dropped too.
+  pub fn wait(self) {
+    // A plain clone shares the counter without adding a participant.
+    let wg = self.wg.clone();
+    drop(self);
+    wg.wait();
+  }
+}
+
+/// Blocks until every group in `groups` has drained to zero.
+///
+/// The groups are waited on one after another, so each of them has been
+/// observed at zero at some point before this returns — the same
+/// guarantee as calling [`WaitGroup::wait`] on each in turn, which is
+/// what this does.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::WaitGroup;
+///
+/// let net = WaitGroup::new();
+/// let disk = WaitGroup::new();
+/// for wg in [&net, &disk] {
+///     let t_wg = wg.add(1);
+///     std::thread::spawn(move || t_wg.done());
+/// }
+///
+/// wg::wait_all([&net, &disk]);
+/// ```
+pub fn wait_all<'a, I>(groups: I)
+where
+  I: IntoIterator<Item = &'a WaitGroup>,
+{
+  for wg in groups {
+    wg.wait();
+  }
+}
+
+/// Blocks until any group in `groups` drains to zero, and returns its
+/// index in `groups`, or `None` if `groups` is empty.
+///
+/// If several groups are drained, the lowest index is returned. The
+/// calling thread registers with every group and parks; the `done` that
+/// brings one of them to zero unparks it. No thread is spawned per group
+/// and `remaining` is not polled.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use wg::WaitGroup;
+///
+/// let slow = WaitGroup::new();
+/// let fast = WaitGroup::new();
+/// let t_slow = slow.add(1);
+/// let t_fast = fast.add(1);
+/// std::thread::spawn(move || {
+///     std::thread::sleep(Duration::from_millis(50));
+///     t_slow.done();
+/// });
+/// std::thread::spawn(move || t_fast.done());
+///
+/// assert_eq!(wg::wait_any([&slow, &fast]), Some(1));
+/// ```
+pub fn wait_any<'a, I>(groups: I) -> Option<usize>
+where
+  I: IntoIterator<Item = &'a WaitGroup>,
+{
+  let groups: Vec<&WaitGroup> = groups.into_iter().collect();
+  if groups.is_empty() {
+    return None;
+  }
+
+  // Fast path: something has drained already.
+  if let Some(idx) = groups.iter().position(|wg| wg.remaining() == 0) {
+    return Some(idx);
+  }
+
+  let _waiting: Vec<_> = groups.iter().map(|wg| wg.inner.waiters().enter()).collect();
+  let me = thread::current();
+  for wg in &groups {
+    wg.inner.watchers().register(me.clone());
+  }
+
+  // Register first, then check: a group that drains after its check
+  // unparks us, and a pending unpark makes the next `park` return.
+  let idx = loop {
+    if let Some(idx) = groups.iter().position(|wg| wg.inner.drained()) {
+      break idx;
+    }
+    thread::park();
+  };
+
+  for wg in &groups {
+    wg.inner.watchers().unregister(me.id());
+  }
+  Some(idx)
 }
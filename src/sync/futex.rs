@@ -0,0 +1,202 @@
+//! Futex backend for the blocking [`WaitGroup`](crate::WaitGroup), enabled
+//! by the `futex` feature.
+//!
+//! The counter is a single atomic, so `add`, `done` and `remaining` never
+//! take a lock. Waiters sleep on a separate 32-bit futex word that `done`
+//! bumps — and wakes — only on the transition to zero.
+
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use super::{panicked::PanicSlot, watch::Watchers};
+use crate::{seal::Seal, waiters::Waiters, Sealed};
+
+pub(super) struct Inner {
+  counter: AtomicUsize,
+  // Futex word. Incremented every time `counter` drops to zero; waiters
+  // sleep while it still holds the value they observed before checking
+  // the counter.
+  epoch: AtomicU32,
+  // Number of threads inside `wait`, so `done` can skip the wake syscall
+  // when nobody is sleeping.
+  sleepers: AtomicU32,
+  // Futex word for `wait_at_most` callers, bumped when the counter drops
+  // to `threshold` or below. A separate word keeps those wake-ups away
+  // from the plain waiters on `epoch`.
+  thr_epoch: AtomicU32,
+  // Largest threshold any sleeping `wait_at_most` caller is waiting for,
+  // `0` if none.
+  threshold: AtomicUsize,
+  seal: Seal,
+  // Number of `reset` calls so far.
+  resets: AtomicUsize,
+  watchers: Watchers,
+  panic: PanicSlot,
+  waiters: Waiters,
+}
+
+impl Inner {
+  pub(super) fn new(count: usize) -> Self {
+    Self {
+      counter: AtomicUsize::new(count),
+      epoch: AtomicU32::new(0),
+      sleepers: AtomicU32::new(0),
+      thr_epoch: AtomicU32::new(0),
+      threshold: AtomicUsize::new(0),
+      seal: Seal::new(),
+      resets: AtomicUsize::new(0),
+      watchers: Watchers::new(),
+      panic: PanicSlot::new(),
+      waiters: Waiters::new(),
+    }
+  }
+
+  pub(super) fn add(&self, num: usize) -> Result<(), Sealed> {
+    self.seal.precheck()?;
+    // `checked_add` in all builds, see the mutex backend.
+    self
+      .counter
+      .fetch_update(Ordering::Release, Ordering::Relaxed, |prev| {
+        prev.checked_add(num)
+      })
+      .expect("WaitGroup counter overflow");
+    if let Err(sealed) = self.seal.check() {
+      self.done(num);
+      return Err(sealed);
+    }
+    Ok(())
+  }
+
+  pub(super) fn seal(&self) {
+    self.seal.seal();
+  }
+
+  pub(super) fn is_sealed(&self) -> bool {
+    self.seal.is_sealed()
+  }
+
+  /// Zeroes the counter and wakes every waiter, whatever it waits for.
+  pub(super) fn reset(&self) {
+    // Published by the `SeqCst` store below.
+    self.resets.fetch_add(1, Ordering::Relaxed);
+    self.counter.store(0, Ordering::SeqCst);
+    self.epoch.fetch_add(1, Ordering::Release);
+    atomic_wait::wake_all(&self.epoch);
+    self.threshold.store(0, Ordering::SeqCst);
+    self.thr_epoch.fetch_add(1, Ordering::Release);
+    atomic_wait::wake_all(&self.thr_epoch);
+    self.watchers.notify();
+  }
+
+  pub(super) fn resets(&self) -> usize {
+    self.resets.load(Ordering::Acquire)
+  }
+
+  pub(super) fn done(&self, num: usize) -> usize {
+    if num == 0 {
+      return self.remaining();
+    }
+
+    // Saturate at zero; an already-zero counter is left untouched.
+    let remaining = match self
+      .counter
+      .fetch_update(Ordering::SeqCst, Ordering::Acquire, |v| {
+        (v != 0).then(|| v.saturating_sub(num))
+      }) {
+      Ok(old) if old <= num => {
+        self.epoch.fetch_add(1, Ordering::Release);
+        // Pairs with the `SeqCst` increment of `sleepers` in `wait`:
+        // either we see the sleeper here, or the sleeper's counter
+        // check sees our decrement and it never goes to sleep.
+        if self.sleepers.load(Ordering::SeqCst) != 0 {
+          atomic_wait::wake_all(&self.epoch);
+        }
+        self.watchers.notify();
+        0
+      }
+      Ok(old) => old - num,
+      // Over-done: counter was already zero. Silently no-op.
+      Err(_) => return 0,
+    };
+
+    // Same pairing for threshold waiters, through their `SeqCst`
+    // `fetch_max` of `threshold`. The `done` that resets the threshold
+    // wakes all of them; those still above their own threshold register
+    // it again.
+    let thr = self.threshold.load(Ordering::SeqCst);
+    if thr != 0 && remaining <= thr && self.threshold.swap(0, Ordering::SeqCst) != 0 {
+      self.thr_epoch.fetch_add(1, Ordering::Release);
+      atomic_wait::wake_all(&self.thr_epoch);
+    }
+    remaining
+  }
+
+  pub(super) fn remaining(&self) -> usize {
+    self.counter.load(Ordering::Acquire)
+  }
+
+  pub(super) fn watchers(&self) -> &Watchers {
+    &self.watchers
+  }
+
+  pub(super) fn panic_slot(&self) -> &PanicSlot {
+    &self.panic
+  }
+
+  pub(super) fn waiters(&self) -> &Waiters {
+    &self.waiters
+  }
+
+  /// Whether the counter is zero. The read-modify-write either comes
+  /// after a racing `done`'s decrement in the counter's modification
+  /// order, and sees it, or before it, in which case that `done`
+  /// synchronizes with us and sees a watcher registered before the
+  /// check.
+  pub(super) fn drained(&self) -> bool {
+    self.counter.fetch_add(0, Ordering::AcqRel) == 0
+  }
+
+  pub(super) fn try_remaining(&self) -> Option<usize> {
+    Some(self.remaining())
+  }
+
+  /// Blocks until the counter is at most `n`. `n` must be non-zero.
+  pub(super) fn wait_at_most(&self, n: usize) {
+    loop {
+      // As in `wait`: read the futex word before publishing the
+      // threshold and re-checking the counter.
+      let epoch = self.thr_epoch.load(Ordering::Acquire);
+      self.threshold.fetch_max(n, Ordering::SeqCst);
+      if self.counter.load(Ordering::SeqCst) <= n {
+        return;
+      }
+      atomic_wait::wait(&self.thr_epoch, epoch);
+    }
+  }
+
+  pub(super) fn wait(&self) {
+    if self.counter.load(Ordering::Acquire) == 0 {
+      return;
+    }
+
+    self.sleepers.fetch_add(1, Ordering::SeqCst);
+    loop {
+      // Read the futex word *before* re-checking the counter: if the
+      // counter drops to zero after the check, the epoch will have
+      // moved and `atomic_wait::wait` returns immediately.
+      let epoch = self.epoch.load(Ordering::Acquire);
+      if self.counter.load(Ordering::SeqCst) == 0 {
+        break;
+      }
+      atomic_wait::wait(&self.epoch, epoch);
+    }
+    self.sleepers.fetch_sub(1, Ordering::Relaxed);
+  }
+}
+
+// A `WeakWaitGroup` waits by registering with `watchers`, without
+// keeping the group alive; this wake-up tells it the last handle is gone.
+impl Drop for Inner {
+  fn drop(&mut self) {
+    self.watchers.notify();
+  }
+}
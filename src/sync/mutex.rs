@@ -0,0 +1,192 @@
+//! `Mutex` + `Condvar` backend for the blocking [`WaitGroup`](crate::WaitGroup).
+
+use super::{panicked::PanicSlot, watch::Watchers};
+use crate::{
+  primitives::{AtomicBool, AtomicUsize, Condvar, Mu, Mutex, Ordering},
+  waiters::Waiters,
+  Sealed,
+};
+
+pub(super) struct Inner {
+  cvar: Condvar,
+  count: Mutex<usize>,
+  // Threshold waiters park here, so decrements that satisfy them don't
+  // wake the plain waiters on `cvar`.
+  thr_cvar: Condvar,
+  // Largest threshold any parked `wait_at_most` caller is waiting for,
+  // `0` if none. Only accessed with `count` locked, hence `Relaxed`.
+  threshold: AtomicUsize,
+  // Only accessed with `count` locked, so `add` and `seal` are ordered
+  // by the lock.
+  sealed: AtomicBool,
+  // Number of `reset` calls so far. Only written with `count` locked.
+  resets: AtomicUsize,
+  watchers: Watchers,
+  panic: PanicSlot,
+  waiters: Waiters,
+}
+
+impl Inner {
+  pub(super) fn new(count: usize) -> Self {
+    Self {
+      cvar: Condvar::new(),
+      count: Mutex::new(count),
+      thr_cvar: Condvar::new(),
+      threshold: AtomicUsize::new(0),
+      sealed: AtomicBool::new(false),
+      resets: AtomicUsize::new(0),
+      watchers: Watchers::new(),
+      panic: PanicSlot::new(),
+      waiters: Waiters::new(),
+    }
+  }
+
+  pub(super) fn add(&self, num: usize) -> Result<(), Sealed> {
+    let mut ctr = self.count.lock_me();
+    if self.sealed.load(Ordering::Relaxed) {
+      return Err(Sealed);
+    }
+    // `checked_add` in all builds — not just debug. A wrap from
+    // usize::MAX + 1 → 0 would reset the counter and let `wait()`
+    // return prematurely. `+=` only panics on overflow in debug mode.
+    *ctr = ctr.checked_add(num).expect("WaitGroup counter overflow");
+    Ok(())
+  }
+
+  pub(super) fn seal(&self) {
+    let _ctr = self.count.lock_me();
+    self.sealed.store(true, Ordering::Relaxed);
+  }
+
+  pub(super) fn is_sealed(&self) -> bool {
+    let _ctr = self.count.lock_me();
+    self.sealed.load(Ordering::Relaxed)
+  }
+
+  /// Zeroes the counter and wakes every waiter, whatever it waits for.
+  pub(super) fn reset(&self) {
+    let mut ctr = self.count.lock_me();
+    *ctr = 0;
+    self.resets.fetch_add(1, Ordering::Relaxed);
+    self.threshold.store(0, Ordering::Relaxed);
+    self.cvar.notify_all();
+    self.thr_cvar.notify_all();
+    drop(ctr);
+    self.watchers.notify();
+  }
+
+  pub(super) fn resets(&self) -> usize {
+    let _ctr = self.count.lock_me();
+    self.resets.load(Ordering::Relaxed)
+  }
+
+  pub(super) fn done(&self, num: usize) -> usize {
+    let mut val = self.count.lock_me();
+
+    let prev = *val;
+    *val = prev.saturating_sub(num);
+    let remaining = *val;
+    // Wake the threshold waiters once the largest threshold is reached.
+    // Those still above their own threshold register it again.
+    let thr = self.threshold.load(Ordering::Relaxed);
+    if thr != 0 && remaining <= thr {
+      self.threshold.store(0, Ordering::Relaxed);
+      self.thr_cvar.notify_all();
+    }
+    // Notify only on the transition to zero, once per batch.
+    if prev != 0 && remaining == 0 {
+      self.cvar.notify_all();
+      drop(val);
+      self.watchers.notify();
+    }
+    remaining
+  }
+
+  pub(super) fn remaining(&self) -> usize {
+    *self.count.lock_me()
+  }
+
+  pub(super) fn watchers(&self) -> &Watchers {
+    &self.watchers
+  }
+
+  pub(super) fn panic_slot(&self) -> &PanicSlot {
+    &self.panic
+  }
+
+  pub(super) fn waiters(&self) -> &Waiters {
+    &self.waiters
+  }
+
+  /// Whether the counter is zero. Taking the lock orders this check
+  /// with a racing `done`, so a watcher registered before the check is
+  /// either told about the transition to zero or sees it here.
+  pub(super) fn drained(&self) -> bool {
+    *self.count.lock_me() == 0
+  }
+
+  /// Like `remaining`, but gives up instead of blocking when the lock is
+  /// contended.
+  pub(super) fn try_remaining(&self) -> Option<usize> {
+    #[cfg(all(feature = "parking_lot", not(loom)))]
+    {
+      self.count.try_lock().map(|ctr| *ctr)
+    }
+
+    #[cfg(any(not(feature = "parking_lot"), loom))]
+    {
+      match self.count.try_lock() {
+        Ok(ctr) => Some(*ctr),
+        Err(std::sync::TryLockError::Poisoned(e)) => Some(*e.into_inner()),
+        Err(std::sync::TryLockError::WouldBlock) => None,
+      }
+    }
+  }
+
+  /// Blocks until the counter is at most `n`. `n` must be non-zero.
+  pub(super) fn wait_at_most(&self, n: usize) {
+    let mut ctr = self.count.lock_me();
+
+    while *ctr > n {
+      self.threshold.fetch_max(n, Ordering::Relaxed);
+
+      #[cfg(all(feature = "parking_lot", not(loom)))]
+      {
+        self.thr_cvar.wait(&mut ctr);
+      }
+
+      #[cfg(any(not(feature = "parking_lot"), loom))]
+      {
+        ctr = self.thr_cvar.wait(ctr).unwrap_or_else(|e| e.into_inner());
+      }
+    }
+  }
+
+  pub(super) fn wait(&self) {
+    let mut ctr = self.count.lock_me();
+
+    if ctr.eq(&0) {
+      return;
+    }
+
+    while *ctr > 0 {
+      #[cfg(all(feature = "parking_lot", not(loom)))]
+      {
+        self.cvar.wait(&mut ctr);
+      }
+
+      #[cfg(any(not(feature = "parking_lot"), loom))]
+      {
+        ctr = self.cvar.wait(ctr).unwrap_or_else(|e| e.into_inner());
+      }
+    }
+  }
+}
+
+// A `WeakWaitGroup` waits by registering with `watchers`, without
+// keeping the group alive; this wake-up tells it the last handle is gone.
+impl Drop for Inner {
+  fn drop(&mut self) {
+    self.watchers.notify();
+  }
+}
@@ -0,0 +1,105 @@
+//! Panic payloads recorded by [`WaitGroup::run`](crate::WaitGroup::run),
+//! and the error [`WaitGroup::join`](crate::WaitGroup::join) reports them
+//! with.
+
+use std::{any::Any, boxed::Box, string::String};
+
+use crate::primitives::{AtomicBool, Mu, Mutex, Ordering};
+
+type Payload = Box<dyn Any + Send + 'static>;
+
+pub(super) struct PanicSlot {
+  // Set once a payload is stored, so `take` skips the lock in the common
+  // case where no worker panicked.
+  set: AtomicBool,
+  payload: Mutex<Option<Payload>>,
+}
+
+impl PanicSlot {
+  pub(super) fn new() -> Self {
+    Self {
+      set: AtomicBool::new(false),
+      payload: Mutex::new(None),
+    }
+  }
+
+  /// Stores `payload` unless an earlier one is still waiting to be
+  /// reported; later payloads are dropped.
+  pub(super) fn record(&self, payload: Payload) {
+    let mut slot = self.payload.lock_me();
+    if slot.is_none() {
+      *slot = Some(payload);
+      self.set.store(true, Ordering::Release);
+      return;
+    }
+    // Drop the extra payload outside the lock: its destructor is user
+    // code and may itself panic.
+    drop(slot);
+    drop(payload);
+  }
+
+  pub(super) fn take(&self) -> Option<Payload> {
+    if !self.set.load(Ordering::Acquire) {
+      return None;
+    }
+    let mut slot = self.payload.lock_me();
+    self.set.store(false, Ordering::Release);
+    slot.take()
+  }
+}
+
+/// The error returned by [`WaitGroup::join`](crate::WaitGroup::join) when
+/// work run through [`WaitGroup::run`](crate::WaitGroup::run) panicked.
+///
+/// It carries the payload of the first such panic. Call
+/// [`resume`](Self::resume) to continue unwinding on the waiting thread,
+/// as if the panic had happened there.
+pub struct WorkerPanicked {
+  payload: Payload,
+}
+
+impl WorkerPanicked {
+  pub(super) fn new(payload: Payload) -> Self {
+    Self { payload }
+  }
+
+  /// Returns the panic message, if the payload is a `&str` or `String` —
+  /// which it is for every `panic!` with a message.
+  pub fn message(&self) -> Option<&str> {
+    if let Some(s) = self.payload.downcast_ref::<&'static str>() {
+      Some(s)
+    } else {
+      self.payload.downcast_ref::<String>().map(String::as_str)
+    }
+  }
+
+  /// Returns the panic payload, as passed to
+  /// [`std::panic::resume_unwind`].
+  pub fn into_payload(self) -> Box<dyn Any + Send + 'static> {
+    self.payload
+  }
+
+  /// Resumes unwinding with the recorded payload on the current thread.
+  pub fn resume(self) -> ! {
+    std::panic::resume_unwind(self.payload)
+  }
+}
+
+impl core::fmt::Debug for WorkerPanicked {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("WorkerPanicked")
+      .field("message", &self.message())
+      .finish_non_exhaustive()
+  }
+}
+
+impl core::fmt::Display for WorkerPanicked {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self.message() {
+      Some(msg) => write!(f, "a WaitGroup worker panicked: {msg}"),
+      None => f.write_str("a WaitGroup worker panicked"),
+    }
+  }
+}
+
+impl std::error::Error for WorkerPanicked {}
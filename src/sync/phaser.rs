@@ -0,0 +1,198 @@
+use crate::{
+  phaser::{Core, Notify},
+  primitives::{Arc, Condvar, Mu},
+};
+
+impl Notify for Condvar {
+  fn notify_advance(&self) {
+    self.notify_all();
+  }
+}
+
+/// A reusable barrier with a dynamic number of parties and numbered
+/// phases, in the style of Java's `Phaser`.
+///
+/// Parties [`register`](Self::register) at any time and join the current
+/// phase. Once every registered party has arrived, through
+/// [`arrive`](Self::arrive), [`arrive_and_deregister`] or
+/// [`arrive_and_await`](Self::arrive_and_await), the phase number goes up
+/// by one and the next phase starts with the parties still registered.
+/// Any thread can block until a given phase has completed with
+/// [`await_phase`](Self::await_phase), whether it is a party or not.
+///
+/// When the last party deregisters, the phaser terminates: waiters are
+/// released, and [`register`](Self::register) returns `None` from then
+/// on.
+///
+/// # Tiering
+///
+/// With many parties, all arrivals contend on one lock. A phaser created
+/// with [`child`](Self::child) shares its parent's phases but counts its
+/// own parties, and arrives at the parent as a single party once all of
+/// them have arrived. Children can be nested to any depth.
+///
+/// With the `future` feature, `future::Phaser` is the async variant.
+///
+/// # Example
+///
+/// ```rust
+/// use wg::Phaser;
+///
+/// let phaser = Phaser::new(1);
+/// let mut workers = Vec::new();
+/// for _ in 0..3 {
+///     let p = phaser.clone();
+///     p.register().unwrap();
+///     workers.push(std::thread::spawn(move || {
+///         for _ in 0..2 {
+///             // one step of the simulation
+///             p.arrive_and_await();
+///         }
+///         p.arrive_and_deregister();
+///     }));
+/// }
+///
+/// assert_eq!(phaser.arrive_and_await(), 1);
+/// assert_eq!(phaser.arrive_and_await(), 2);
+/// phaser.arrive_and_deregister();
+/// for w in workers {
+///     w.join().unwrap();
+/// }
+/// assert!(phaser.is_terminated());
+/// ```
+///
+/// [`arrive_and_deregister`]: Self::arrive_and_deregister
+#[derive(Clone)]
+pub struct Phaser {
+  core: Arc<Core<Condvar>>,
+}
+
+impl Default for Phaser {
+  fn default() -> Self {
+    Self::new(0)
+  }
+}
+
+impl core::fmt::Debug for Phaser {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Phaser")
+      .field("phase", &self.phase())
+      .field("registered", &self.registered())
+      .field("unarrived", &self.unarrived())
+      .finish()
+  }
+}
+
+impl Phaser {
+  /// Creates a phaser in phase `0` with `parties` registered parties.
+  pub fn new(parties: usize) -> Self {
+    Self {
+      core: Arc::new(Core::new(parties, Condvar::new())),
+    }
+  }
+
+  /// Creates a child phaser with `parties` registered parties, tiered
+  /// under this one.
+  ///
+  /// The child follows this phaser's phases. While it has parties, it is
+  /// registered here as one party, which arrives once all of the child's
+  /// parties have.
+  pub fn child(&self, parties: usize) -> Self {
+    Self {
+      core: Arc::new(Core::child(&self.core, parties, Condvar::new())),
+    }
+  }
+
+  /// Registers a new party in the current phase and returns the phase
+  /// number, or `None` if the phaser has terminated.
+  pub fn register(&self) -> Option<u64> {
+    self.core.register(1)
+  }
+
+  /// Registers `num` new parties at once, like
+  /// [`register`](Self::register).
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of parties would overflow.
+  pub fn register_n(&self, num: usize) -> Option<u64> {
+    self.core.register(num)
+  }
+
+  /// Records the arrival of one party without waiting for the others,
+  /// and returns the phase it arrived in.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every registered party has already arrived in the
+  /// current phase.
+  pub fn arrive(&self) -> u64 {
+    self.core.arrive(false)
+  }
+
+  /// Records the arrival of one party and deregisters it, so the next
+  /// phases no longer wait for it. Returns the phase it arrived in.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every registered party has already arrived in the
+  /// current phase.
+  pub fn arrive_and_deregister(&self) -> u64 {
+    self.core.arrive(true)
+  }
+
+  /// Records the arrival of one party and blocks until every other party
+  /// has arrived as well. Returns the number of the phase that starts.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every registered party has already arrived in the
+  /// current phase.
+  pub fn arrive_and_await(&self) -> u64 {
+    let phase = self.arrive();
+    self.await_phase(phase)
+  }
+
+  /// Blocks until phase `phase` has completed or the phaser has
+  /// terminated, and returns the current phase number.
+  ///
+  /// Returns immediately for a phase that has already completed.
+  pub fn await_phase(&self, phase: u64) -> u64 {
+    let root = self.core.root();
+    let mut st = root.state.lock_me();
+    while st.phase <= phase && !st.terminated {
+      #[cfg(all(feature = "parking_lot", not(loom)))]
+      {
+        root.notify.wait(&mut st);
+      }
+
+      #[cfg(any(not(feature = "parking_lot"), loom))]
+      {
+        st = root.notify.wait(st).unwrap_or_else(|e| e.into_inner());
+      }
+    }
+    st.phase
+  }
+
+  /// Returns the current phase number.
+  pub fn phase(&self) -> u64 {
+    self.core.phase()
+  }
+
+  /// Returns the number of parties registered on this phaser. For a
+  /// parent, each child with parties counts as one.
+  pub fn registered(&self) -> usize {
+    self.core.registered()
+  }
+
+  /// Returns the number of registered parties that have not arrived in
+  /// the current phase yet.
+  pub fn unarrived(&self) -> usize {
+    self.core.unarrived()
+  }
+
+  /// Returns `true` once the last party has deregistered.
+  pub fn is_terminated(&self) -> bool {
+    self.core.is_terminated()
+  }
+}
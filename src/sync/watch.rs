@@ -0,0 +1,46 @@
+//! Threads blocked in [`wait_any`](crate::wait_any), registered with each
+//! group they watch.
+
+use std::vec::Vec;
+
+use crate::primitives::{thread, AtomicUsize, Mu, Mutex, Ordering};
+
+pub(super) struct Watchers {
+  // Number of registered threads, so `notify` skips the lock in the
+  // common case where nobody is watching.
+  len: AtomicUsize,
+  threads: Mutex<Vec<thread::Thread>>,
+}
+
+impl Watchers {
+  pub(super) fn new() -> Self {
+    Self {
+      len: AtomicUsize::new(0),
+      threads: Mutex::new(Vec::new()),
+    }
+  }
+
+  pub(super) fn register(&self, thread: thread::Thread) {
+    self.threads.lock_me().push(thread);
+    self.len.fetch_add(1, Ordering::AcqRel);
+  }
+
+  pub(super) fn unregister(&self, id: thread::ThreadId) {
+    let mut threads = self.threads.lock_me();
+    if let Some(pos) = threads.iter().position(|t| t.id() == id) {
+      threads.swap_remove(pos);
+      self.len.fetch_sub(1, Ordering::AcqRel);
+    }
+  }
+
+  /// Unparks every registered thread. Called by the `done` that brings
+  /// the counter to zero, and when the group itself is dropped.
+  pub(super) fn notify(&self) {
+    if self.len.load(Ordering::Acquire) == 0 {
+      return;
+    }
+    for thread in self.threads.lock_me().iter() {
+      thread.unpark();
+    }
+  }
+}
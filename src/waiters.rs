@@ -0,0 +1,103 @@
+//! The count of blocked waiters behind `waiters()` on each variant.
+//!
+//! A waiter holds a [`Waiting`] guard for as long as it is blocked, so the
+//! count stays right whether the wait returns, panics, or — for a future —
+//! is cancelled by dropping it.
+
+use crate::primitives::{AtomicUsize, Ordering};
+
+#[derive(Debug)]
+pub(crate) struct Waiters(AtomicUsize);
+
+impl Waiters {
+  pub(crate) fn new() -> Self {
+    Self(AtomicUsize::new(0))
+  }
+
+  /// Counts the caller as a waiter until the returned guard is dropped.
+  pub(crate) fn enter(&self) -> Waiting<'_> {
+    self.arrive();
+    Waiting(self)
+  }
+
+  /// Counts a waiter without a guard, for one that cannot borrow the
+  /// group while it waits. It must [`leave`](Self::leave) again unless
+  /// the group is gone by then.
+  pub(crate) fn arrive(&self) {
+    // The count is a diagnostic, it orders nothing else.
+    self.0.fetch_add(1, Ordering::Relaxed);
+  }
+
+  /// Stops counting a waiter that was counted without a guard.
+  pub(crate) fn leave(&self) {
+    self.0.fetch_sub(1, Ordering::Relaxed);
+  }
+
+  pub(crate) fn count(&self) -> usize {
+    self.0.load(Ordering::Relaxed)
+  }
+
+  /// Passes a future's `poll` result through, counting the future as a
+  /// waiter in `waiting` while it is pending. Dropping the future drops
+  /// the guard with it.
+  #[cfg(feature = "future")]
+  pub(crate) fn track<'a, T>(
+    &'a self,
+    waiting: &mut Option<Waiting<'a>>,
+    poll: core::task::Poll<T>,
+  ) -> core::task::Poll<T> {
+    if poll.is_pending() {
+      waiting.get_or_insert_with(|| self.enter());
+    } else {
+      *waiting = None;
+    }
+    poll
+  }
+
+  /// Like [`track`](Self::track), for a waiter that only holds a `Weak`
+  /// to the group and so cannot hold a [`Waiting`] borrowing it:
+  /// `counted` records whether it is counted, and the owner must call
+  /// [`untrack`](Self::untrack) when it is dropped.
+  #[cfg(all(
+    feature = "future",
+    not(loom),
+    any(feature = "portable-atomic", not(feature = "triomphe"))
+  ))]
+  pub(crate) fn track_owned<T>(
+    &self,
+    counted: &mut bool,
+    poll: core::task::Poll<T>,
+  ) -> core::task::Poll<T> {
+    if poll.is_pending() {
+      if !*counted {
+        self.arrive();
+        *counted = true;
+      }
+    } else {
+      self.untrack(counted);
+    }
+    poll
+  }
+
+  /// Stops counting a waiter tracked by [`track_owned`](Self::track_owned).
+  #[cfg(all(
+    feature = "future",
+    not(loom),
+    any(feature = "portable-atomic", not(feature = "triomphe"))
+  ))]
+  pub(crate) fn untrack(&self, counted: &mut bool) {
+    if core::mem::take(counted) {
+      self.leave();
+    }
+  }
+}
+
+/// One blocked waiter, counted in its [`Waiters`] until dropped.
+#[derive(Debug)]
+pub(crate) struct Waiting<'a>(&'a Waiters);
+
+impl Drop for Waiting<'_> {
+  fn drop(&mut self) {
+    self.0.leave();
+  }
+}
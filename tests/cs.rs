@@ -0,0 +1,102 @@
+use wg::cs::WaitGroup;
+
+#[test]
+fn static_group() {
+  static WG: WaitGroup = WaitGroup::new();
+
+  WG.add(3);
+  let handles: Vec<_> = (0..3)
+    .map(|_| {
+      std::thread::spawn(|| {
+        WG.done();
+      })
+    })
+    .collect();
+
+  WG.wait();
+  assert_eq!(WG.remaining(), 0);
+  for h in handles {
+    h.join().unwrap();
+  }
+}
+
+#[test]
+fn add_chains_and_done_returns_remaining() {
+  let wg = WaitGroup::new();
+  assert_eq!(wg.add(1).add(1).remaining(), 2);
+  assert_eq!(wg.done(), 1);
+  assert_eq!(wg.done(), 0);
+  // Over-done is a silent no-op, returns 0.
+  assert_eq!(wg.done(), 0);
+}
+
+#[test]
+fn done_n_saturates() {
+  let wg = WaitGroup::from(5);
+  assert_eq!(wg.done_n(2), 3);
+  assert_eq!(wg.done_n(10), 0);
+  assert_eq!(wg.done_n(1), 0);
+}
+
+#[test]
+#[should_panic(expected = "WaitGroup counter overflow")]
+fn add_overflow_panics() {
+  let wg = WaitGroup::from(usize::MAX);
+  wg.add(1);
+}
+
+#[test]
+fn wait_idle_runs_until_zero() {
+  // Each idle call stands in for an interrupt that completes one unit.
+  let wg = WaitGroup::from(3);
+  let mut idles = 0;
+  wg.wait_idle(|| {
+    idles += 1;
+    wg.done();
+  });
+  assert_eq!(idles, 3);
+  assert_eq!(wg.remaining(), 0);
+}
+
+#[test]
+fn wait_idle_returns_immediately_at_zero() {
+  let wg = WaitGroup::new();
+  wg.wait_idle(|| panic!("idle called with a zero counter"));
+}
+
+#[test]
+fn wait_with_custom_relax() {
+  static WG: WaitGroup = WaitGroup::new();
+
+  WG.add(1);
+  std::thread::spawn(|| {
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    WG.done();
+  });
+  WG.wait_with(std::thread::yield_now);
+  assert_eq!(WG.remaining(), 0);
+}
+
+#[test]
+fn waiters_counts_blocked_callers() {
+  let wg = WaitGroup::from(1);
+  assert_eq!(wg.waiters(), 0);
+  wg.wait_idle(|| {
+    assert_eq!(wg.waiters(), 1);
+    wg.done();
+  });
+  assert_eq!(wg.waiters(), 0);
+
+  wg.add(1);
+  wg.wait_with(|| {
+    assert_eq!(wg.waiters(), 1);
+    wg.done();
+  });
+  assert_eq!(wg.waiters(), 0);
+}
+
+#[test]
+fn debug() {
+  let wg = WaitGroup::from(2);
+  assert_eq!(format!("{wg:?}"), "WaitGroup { counter: 2 }");
+}
@@ -6,7 +6,7 @@ extern crate std;
 #[cfg(all(feature = "alloc", not(feature = "std")))]
 extern crate alloc;
 
-use wg::future::WaitGroup;
+use wg::future::{PollWait, WaitGroup};
 
 // ==================================================================
 // Tests that require std (runtime, timers, threads, Arc, AtomicUsize)
@@ -173,6 +173,53 @@ mod std_tests {
       smol::block_on(block_wait_in::<agnostic_lite::smol::SmolSpawner>())
     }
 
+    /// Threads and tasks share one counter: a blocking waiter and an async
+    /// waiter are both released by the same `done()`, whichever side it
+    /// comes from.
+    async fn mixed_waiters_in<S: RuntimeLite>() {
+      for from_thread in [true, false] {
+        let wg = WaitGroup::new();
+        let t_wg = wg.add(1);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let blocking = {
+          let wg = wg.clone();
+          std::thread::spawn(move || {
+            wg.wait_blocking();
+            tx.send(()).unwrap();
+          })
+        };
+
+        if from_thread {
+          std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            t_wg.done();
+          });
+        } else {
+          S::spawn_detach(async move {
+            S::sleep(Duration::from_millis(20)).await;
+            t_wg.done();
+          });
+        }
+
+        wg.wait().await;
+        rx.recv_timeout(Duration::from_secs(5))
+          .expect("blocking waiter was not woken by done()");
+        blocking.join().unwrap();
+        assert_eq!(wg.remaining(), 0);
+      }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mixed_waiters_tokio() {
+      mixed_waiters_in::<agnostic_lite::tokio::TokioRuntime>().await;
+    }
+
+    #[test]
+    fn mixed_waiters_smol() {
+      smol::block_on(mixed_waiters_in::<agnostic_lite::smol::SmolRuntime>())
+    }
+
     #[test]
     fn test_wait_blocking_on_zero_returns_immediately() {
       let wg = WaitGroup::new();
@@ -217,6 +264,280 @@ mod std_tests {
   fn wake_after_updating_smol() {
     smol::block_on(wake_after_updating_in::<agnostic_lite::smol::SmolRuntime>())
   }
+
+  async fn wait_for_at_most_in<S: RuntimeLite>() {
+    let wg = WaitGroup::new();
+    let t_wg = wg.add(10);
+
+    // Waiters with different thresholds, including a plain one.
+    let waiters = WaitGroup::new();
+    let (tx, rx) = std::sync::mpsc::channel();
+    for n in [7, 3, 0] {
+      let wg = wg.clone();
+      let w = waiters.add(1);
+      let tx = tx.clone();
+      S::spawn_detach(async move {
+        wg.wait_for_at_most(n).await;
+        tx.send((n, wg.remaining())).unwrap();
+        w.done();
+      });
+    }
+
+    for _ in 0..10 {
+      S::sleep(Duration::from_millis(2)).await;
+      t_wg.done();
+    }
+    waiters.wait().await;
+    let mut seen: Vec<_> = rx
+      .try_iter()
+      .map(|(n, remaining)| {
+        assert!(remaining <= n);
+        n
+      })
+      .collect();
+    seen.sort_unstable();
+    assert_eq!(seen, [0, 3, 7]);
+  }
+
+  async fn wait_all_any_in<S: RuntimeLite>() {
+    assert_eq!(wg::future::wait_any([]).await, None);
+    wg::future::wait_all([]).await;
+
+    let slow = WaitGroup::new();
+    let fast = WaitGroup::new();
+    let t_slow = slow.add(1);
+    let t_fast = fast.add(1);
+    S::spawn_detach(async move {
+      S::sleep(Duration::from_millis(50)).await;
+      t_slow.done();
+    });
+    S::spawn_detach(async move {
+      S::sleep(Duration::from_millis(5)).await;
+      t_fast.done();
+    });
+
+    assert_eq!(wg::future::wait_any([&slow, &fast]).await, Some(1));
+    assert!(slow.remaining() == 1);
+    wg::future::wait_all([&slow, &fast]).await;
+    assert_eq!(slow.remaining(), 0);
+    assert_eq!(wg::future::wait_any([&slow, &fast]).await, Some(0));
+  }
+
+  async fn auto_in<S: RuntimeLite>() {
+    let wg = wg::future::AutoWaitGroup::new();
+    let ctr = Arc::new(AtomicUsize::new(0));
+    for _ in 0..5 {
+      let wg = wg.clone();
+      let ctrx = ctr.clone();
+      S::spawn_detach(async move {
+        S::sleep(Duration::from_millis(5)).await;
+        ctrx.fetch_add(1, Ordering::Relaxed);
+        drop(wg);
+      });
+    }
+    assert_eq!(wg.participants(), 6);
+    wg.wait().await;
+    assert_eq!(ctr.load(Ordering::Relaxed), 5);
+  }
+
+  async fn tokens_in<S: RuntimeLite>() {
+    let wg = WaitGroup::new();
+    let (a, b) = wg.add_tokens(3).split(1);
+    S::spawn_detach(async move {
+      S::sleep(Duration::from_millis(5)).await;
+      a.done();
+    });
+    S::spawn_detach(async move {
+      S::sleep(Duration::from_millis(5)).await;
+      b.done();
+    });
+    wg.wait().await;
+    assert_eq!(wg.remaining(), 0);
+  }
+
+  #[tokio::test]
+  async fn tokens_tokio() {
+    tokens_in::<agnostic_lite::tokio::TokioRuntime>().await;
+  }
+
+  #[test]
+  fn tokens_smol() {
+    smol::block_on(tokens_in::<agnostic_lite::smol::SmolRuntime>())
+  }
+
+  fn visit<S: RuntimeLite>(depth: u32, token: wg::future::Token, seen: Arc<AtomicUsize>) {
+    S::spawn_detach(async move {
+      S::yield_now().await;
+      seen.fetch_add(1, Ordering::Relaxed);
+      if depth > 0 {
+        for _ in 0..2 {
+          visit::<S>(depth - 1, token.fork(1), seen.clone());
+        }
+      }
+      token.done();
+    });
+  }
+
+  async fn forked_tokens_in<S: RuntimeLite>() {
+    let wg = WaitGroup::new();
+    let seen = Arc::new(AtomicUsize::new(0));
+    visit::<S>(4, wg.add_token(), seen.clone());
+    wg.wait().await;
+    assert_eq!(seen.load(Ordering::Relaxed), 31);
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn forked_tokens_tokio() {
+    forked_tokens_in::<agnostic_lite::tokio::TokioRuntime>().await;
+  }
+
+  #[test]
+  fn forked_tokens_smol() {
+    smol::block_on(forked_tokens_in::<agnostic_lite::smol::SmolRuntime>())
+  }
+
+  async fn seal_and_reset_in<S: RuntimeLite>() {
+    let wg = WaitGroup::new();
+    let t_wg = wg.add(1);
+    wg.seal();
+    assert!(wg.is_sealed());
+    assert_eq!(wg.try_add(1).unwrap_err(), wg::Sealed);
+    assert_eq!(wg.remaining(), 1);
+
+    S::spawn_detach(async move {
+      S::sleep(Duration::from_millis(5)).await;
+      t_wg.done();
+    });
+    assert_eq!(wg.wait_outcome().await, wg::WaitOutcome::Drained);
+
+    // Sealing is permanent, so reset a fresh group.
+    let wg = WaitGroup::new();
+    wg.add(2);
+    let waiter = wg.clone();
+    let waiter = S::spawn(async move { waiter.wait_outcome().await });
+    while wg.waiters() == 0 {
+      S::yield_now().await;
+    }
+    wg.reset();
+    assert_eq!(waiter.await.unwrap(), wg::WaitOutcome::Reset);
+    assert_eq!(wg.remaining(), 0);
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn seal_and_reset_tokio() {
+    seal_and_reset_in::<agnostic_lite::tokio::TokioRuntime>().await;
+  }
+
+  #[test]
+  fn seal_and_reset_smol() {
+    smol::block_on(seal_and_reset_in::<agnostic_lite::smol::SmolRuntime>())
+  }
+
+  #[test]
+  fn waiters_counts_blocking_callers() {
+    let wg = WaitGroup::new();
+    let t_wg = wg.add(1);
+    let waiters: Vec<_> = (0..2)
+      .map(|i| {
+        let wg = wg.clone();
+        std::thread::spawn(move || {
+          if i == 0 {
+            wg.wait_blocking();
+          } else {
+            assert!(wg.wait_blocking_timeout(Duration::from_secs(60)));
+          }
+        })
+      })
+      .collect();
+
+    while wg.waiters() < 2 {
+      std::thread::yield_now();
+    }
+    t_wg.done();
+    for w in waiters {
+      w.join().unwrap();
+    }
+    assert_eq!(wg.waiters(), 0);
+  }
+
+  #[tokio::test]
+  async fn track_counts_until_completion_or_cancellation() {
+    let wg = WaitGroup::new();
+
+    let out = wg.track(async { 7 });
+    assert_eq!(wg.remaining(), 1);
+    assert_eq!(out.await, 7);
+    assert_eq!(wg.remaining(), 0);
+
+    // A tracked future that loses a `select!` is dropped, which counts as
+    // done.
+    let never = wg.track(core::future::pending::<()>());
+    tokio::select! {
+      _ = never => unreachable!(),
+      _ = tokio::time::sleep(Duration::from_millis(5)) => {}
+    }
+    assert_eq!(wg.remaining(), 0);
+
+    for i in 0..4 {
+      tokio::spawn(wg.track(async move {
+        tokio::time::sleep(Duration::from_millis(5 * i)).await;
+      }));
+    }
+    wg.wait().await;
+  }
+
+  #[cfg(feature = "stream")]
+  #[test]
+  fn track_stream_counts_until_exhausted() {
+    use smol::stream::{Stream, StreamExt};
+
+    smol::block_on(async {
+      let wg = WaitGroup::new();
+      let mut s = wg.track_stream(smol::stream::iter([1, 2]));
+      assert_eq!(s.size_hint(), (2, Some(2)));
+      assert_eq!(s.next().await, Some(1));
+      assert_eq!(s.next().await, Some(2));
+      assert_eq!(wg.remaining(), 1);
+      assert_eq!(s.next().await, None);
+      assert_eq!(wg.remaining(), 0);
+
+      // Dropping an unfinished stream counts as done, too.
+      let s = wg.track_stream(smol::stream::repeat(0));
+      assert_eq!(wg.remaining(), 1);
+      drop(s);
+      wg.wait().await;
+    });
+  }
+
+  #[tokio::test]
+  async fn auto_tokio() {
+    auto_in::<agnostic_lite::tokio::TokioRuntime>().await;
+  }
+
+  #[test]
+  fn auto_smol() {
+    smol::block_on(auto_in::<agnostic_lite::smol::SmolRuntime>())
+  }
+
+  #[tokio::test]
+  async fn wait_all_any_tokio() {
+    wait_all_any_in::<agnostic_lite::tokio::TokioRuntime>().await;
+  }
+
+  #[test]
+  fn wait_all_any_smol() {
+    smol::block_on(wait_all_any_in::<agnostic_lite::smol::SmolRuntime>())
+  }
+
+  #[tokio::test]
+  async fn wait_for_at_most_tokio() {
+    wait_for_at_most_in::<agnostic_lite::tokio::TokioRuntime>().await;
+  }
+
+  #[test]
+  fn wait_for_at_most_smol() {
+    smol::block_on(wait_for_at_most_in::<agnostic_lite::smol::SmolRuntime>())
+  }
 }
 
 // ==================================================================
@@ -256,6 +577,17 @@ fn test_add_assign() {
   assert_eq!(wg.remaining(), 3);
 }
 
+#[test]
+fn test_done_n() {
+  let mut wg = WaitGroup::from(10);
+  assert_eq!(wg.done_n(3), 7);
+  wg -= 2;
+  assert_eq!(wg.remaining(), 5);
+  // Underflow saturates instead of wrapping or panicking.
+  assert_eq!(wg.done_n(100), 0);
+  assert_eq!(wg.done_n(1), 0);
+}
+
 // --------------------------------------------------------------------
 // Manual-polling tests — exercise the race-dependent branches in
 // `WaitGroupFuture::poll`. Uses core::task, not std::task, so they
@@ -307,4 +639,244 @@ mod manual_poll {
       Poll::Ready(())
     ));
   }
+
+  #[test]
+  fn waiters_counts_pending_futures_until_done_or_dropped() {
+    let wg = WaitGroup::new();
+    wg.add(2);
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    // Created but never polled: not registered yet.
+    let mut wait = Box::pin(wg.wait());
+    assert_eq!(wg.waiters(), 0);
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_pending());
+    // Polling again does not count it twice.
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_pending());
+    assert_eq!(wg.waiters(), 1);
+
+    let mut at_most = Box::pin(wg.wait_for_at_most(1));
+    let mut outcome = Box::pin(wg.wait_outcome());
+    assert!(Pin::as_mut(&mut at_most).poll(&mut cx).is_pending());
+    assert!(Pin::as_mut(&mut outcome).poll(&mut cx).is_pending());
+    assert_eq!(wg.waiters(), 3);
+
+    // Cancelled waits stop counting.
+    drop(outcome);
+    assert_eq!(wg.waiters(), 2);
+
+    wg.done();
+    assert!(Pin::as_mut(&mut at_most).poll(&mut cx).is_ready());
+    assert_eq!(wg.waiters(), 1);
+    wg.done();
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_ready());
+    assert_eq!(wg.waiters(), 0);
+    drop(at_most);
+    drop(wait);
+    assert_eq!(wg.waiters(), 0);
+  }
+
+  #[cfg(any(feature = "portable-atomic", not(feature = "triomphe")))]
+  #[test]
+  fn waiters_counts_weak_waits() {
+    let wg = WaitGroup::from(1);
+    let weak = wg.downgrade();
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    let mut wait = Box::pin(weak.wait());
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_pending());
+    assert_eq!(wg.waiters(), 1);
+    drop(wait);
+    assert_eq!(wg.waiters(), 0);
+
+    let mut wait = Box::pin(weak.wait());
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_pending());
+    wg.done();
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_ready());
+    assert_eq!(wg.waiters(), 0);
+  }
+
+  #[test]
+  fn waiters_counts_poll_wait_and_combinators() {
+    let wg = WaitGroup::from(1);
+    let other = WaitGroup::from(1);
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    // A `PollWait` counts from its first pending `poll_wait` until it is
+    // ready or dropped.
+    let mut polled = PollWait::new();
+    assert!(wg.poll_wait(&mut polled, &mut cx).is_pending());
+    assert!(wg.poll_wait(&mut polled, &mut cx).is_pending());
+    assert_eq!(wg.waiters(), 1);
+    drop(polled);
+    assert_eq!(wg.waiters(), 0);
+
+    // Polled against another group, it moves over.
+    let mut polled = PollWait::new();
+    assert!(other.poll_wait(&mut polled, &mut cx).is_pending());
+    assert_eq!((wg.waiters(), other.waiters()), (0, 1));
+    let mut all = Box::pin(wg::future::wait_all([&other, &wg]));
+    let mut any = Box::pin(wg::future::wait_any([&wg, &other]));
+    assert!(wg.poll_wait(&mut polled, &mut cx).is_pending());
+    assert!(Pin::as_mut(&mut all).poll(&mut cx).is_pending());
+    assert!(Pin::as_mut(&mut any).poll(&mut cx).is_pending());
+    // `wait_all` only counts on the group it is waiting for.
+    assert_eq!((wg.waiters(), other.waiters()), (2, 2));
+
+    other.done();
+    assert_eq!(Pin::as_mut(&mut any).poll(&mut cx), Poll::Ready(Some(1)));
+    assert!(Pin::as_mut(&mut all).poll(&mut cx).is_pending());
+    assert_eq!((wg.waiters(), other.waiters()), (2, 0));
+
+    wg.done();
+    assert!(wg.poll_wait(&mut polled, &mut cx).is_ready());
+    assert!(Pin::as_mut(&mut all).poll(&mut cx).is_ready());
+    assert_eq!(wg.waiters(), 0);
+  }
+
+  #[test]
+  fn auto_wait_group_counts_pending_waits() {
+    let wg = wg::future::AutoWaitGroup::new();
+    let participant = wg.clone();
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    let mut wait = Box::pin(wg.wait());
+    assert_eq!(participant.waiters(), 0);
+    assert!(Pin::as_mut(&mut wait).poll(&mut cx).is_pending());
+    assert_eq!(participant.waiters(), 1);
+    drop(wait);
+    assert_eq!(participant.waiters(), 0);
+  }
+
+  struct CountingWaker(core::sync::atomic::AtomicUsize);
+
+  impl alloc::task::Wake for CountingWaker {
+    fn wake(self: alloc::sync::Arc<Self>) {
+      self.0.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+    }
+  }
+
+  fn counting_waker() -> (alloc::sync::Arc<CountingWaker>, Waker) {
+    let w = alloc::sync::Arc::new(CountingWaker(core::sync::atomic::AtomicUsize::new(0)));
+    (w.clone(), Waker::from(w))
+  }
+
+  #[test]
+  fn poll_wait_registers_per_state() {
+    use core::sync::atomic::Ordering;
+
+    let wg = WaitGroup::from(2);
+    let (mut wait, mut other) = (PollWait::new(), PollWait::new());
+    let (count, waker) = counting_waker();
+    let (other_count, other_waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    // Both states poll through the same shared handle.
+    assert!(wg.poll_wait(&mut wait, &mut cx).is_pending());
+    // Re-polling keeps a single registration.
+    assert!(wg.poll_wait(&mut wait, &mut cx).is_pending());
+    assert!(wg
+      .poll_wait(&mut other, &mut Context::from_waker(&other_waker))
+      .is_pending());
+
+    wg.done();
+    assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    wg.done();
+    // Both states' tasks are woken, once each.
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    assert_eq!(other_count.0.load(Ordering::SeqCst), 1);
+    assert!(wg.poll_wait(&mut wait, &mut cx).is_ready());
+    assert!(wg.poll_wait(&mut other, &mut cx).is_ready());
+
+    // Reusable for a new round.
+    wg.add(1);
+    assert!(wg.poll_wait(&mut wait, &mut cx).is_pending());
+    wg.done();
+    assert_eq!(count.0.load(Ordering::SeqCst), 2);
+    assert!(wg.poll_wait(&mut wait, &mut cx).is_ready());
+  }
+
+  #[test]
+  fn wait_any_is_woken_by_the_group_that_drains() {
+    use core::sync::atomic::Ordering;
+
+    let a = WaitGroup::from(1);
+    let b = WaitGroup::from(2);
+    let (count, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    let mut any = wg::future::wait_any([&a, &b]);
+    assert!(Pin::new(&mut any).poll(&mut cx).is_pending());
+
+    // A decrement that doesn't drain `b` doesn't wake us.
+    b.done();
+    assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    b.done();
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    assert_eq!(Pin::new(&mut any).poll(&mut cx), Poll::Ready(Some(1)));
+  }
+
+  #[cfg(any(feature = "portable-atomic", not(feature = "triomphe")))]
+  #[test]
+  fn weak_wait_is_woken_when_the_last_handle_drops() {
+    use core::sync::atomic::Ordering;
+
+    let wg = WaitGroup::from(1);
+    let t_wg = wg.clone();
+    let weak = wg.downgrade();
+    let (count, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    let mut wait = weak.wait();
+    assert!(Pin::new(&mut wait).poll(&mut cx).is_pending());
+
+    drop(wg);
+    assert_eq!(count.0.load(Ordering::SeqCst), 0);
+    assert!(weak.upgrade().is_some());
+
+    // The last handle leaves without calling `done`.
+    drop(t_wg);
+    assert_eq!(count.0.load(Ordering::SeqCst), 1);
+    assert!(Pin::new(&mut wait).poll(&mut cx).is_ready());
+    assert_eq!(weak.remaining(), 0);
+    assert!(weak.upgrade().is_none());
+  }
+
+  #[test]
+  fn threshold_and_plain_waiters_are_woken_separately() {
+    use core::sync::atomic::Ordering;
+
+    let wg = WaitGroup::from(4);
+    let (thr_count, thr_waker) = counting_waker();
+    let (plain_count, plain_waker) = counting_waker();
+
+    let mut thr = wg.wait_for_at_most(2);
+    let mut plain = Box::pin(wg.wait());
+    assert!(Pin::new(&mut thr)
+      .poll(&mut Context::from_waker(&thr_waker))
+      .is_pending());
+    assert!(Pin::as_mut(&mut plain)
+      .poll(&mut Context::from_waker(&plain_waker))
+      .is_pending());
+
+    // 4 -> 3: above the threshold, nobody is woken.
+    wg.done();
+    assert_eq!(thr_count.0.load(Ordering::SeqCst), 0);
+
+    // 3 -> 2: the threshold waiter is woken, the plain one is not.
+    wg.done();
+    assert_eq!(thr_count.0.load(Ordering::SeqCst), 1);
+    assert_eq!(plain_count.0.load(Ordering::SeqCst), 0);
+    assert!(Pin::new(&mut thr)
+      .poll(&mut Context::from_waker(&thr_waker))
+      .is_ready());
+
+    // Further decrements don't touch the finished threshold waiter.
+    wg.done_n(2);
+    assert_eq!(thr_count.0.load(Ordering::SeqCst), 1);
+    assert_eq!(plain_count.0.load(Ordering::SeqCst), 1);
+  }
 }
@@ -0,0 +1,566 @@
+#![cfg(loom)]
+
+//! Model-checks the `add` / `done` / `wait` interleavings of every
+//! WaitGroup variant with [`loom`](https://docs.rs/loom).
+//!
+//! Run with:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --test loom --release --all-features
+//! ```
+//!
+//! Every test has the same shape: workers publish a side effect with a
+//! `Relaxed` store and then call `done`; the waiter asserts that it sees
+//! every side effect once `wait` returns. A premature return fails the
+//! assertion, and a lost wakeup leaves the waiter blocked forever, which
+//! loom reports as a deadlock.
+
+use loom::{
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+  thread,
+};
+
+/// Runs `f` under loom with a default preemption bound, which keeps the
+/// spin-loop and `event-listener` models tractable. `LOOM_MAX_PREEMPTIONS`
+/// overrides it for exhaustive runs.
+fn model<F>(f: F)
+where
+  F: Fn() + Sync + Send + 'static,
+{
+  let mut builder = loom::model::Builder::new();
+  if builder.preemption_bound.is_none() {
+    builder.preemption_bound = Some(3);
+  }
+  builder.check(f);
+}
+
+/// Runs `f` for every worker on its own loom thread. `f` receives the
+/// worker index and the shared side-effect counter.
+fn spawn_workers<F>(workers: usize, effects: &Arc<AtomicUsize>, f: F) -> Vec<thread::JoinHandle<()>>
+where
+  F: Fn(usize, &AtomicUsize) + Send + Sync + 'static,
+{
+  let f = Arc::new(f);
+  (0..workers)
+    .map(|i| {
+      let effects = effects.clone();
+      let f = f.clone();
+      thread::spawn(move || f(i, &effects))
+    })
+    .collect()
+}
+
+#[cfg(feature = "std")]
+mod sync {
+  use super::*;
+  use wg::WaitGroup;
+
+  fn wait_sees_all_workers(spin_rounds: u32) {
+    model(move || {
+      let wg = WaitGroup::new().with_spin_rounds(spin_rounds);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      wg.wait();
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+      assert_eq!(wg.remaining(), 0);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn wait_parks() {
+    wait_sees_all_workers(0);
+  }
+
+  #[test]
+  fn wait_spins_then_parks() {
+    wait_sees_all_workers(1);
+  }
+
+  #[test]
+  fn nested_add_before_done() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let worker = wg.add(1);
+      let handles = spawn_workers(1, &effects, move |_, effects| {
+        // The worker still holds its own unit, so this add can never
+        // let `wait` return early.
+        let nested = worker.add(1);
+        worker.done();
+        effects.fetch_add(1, Ordering::Relaxed);
+        nested.done();
+      });
+
+      wg.wait();
+      assert_eq!(effects.load(Ordering::Relaxed), 1);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn wait_for_at_most_sees_enough_workers() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      wg.wait_for_at_most(1);
+      assert!(effects.load(Ordering::Relaxed) >= 1);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn wait_any_is_unparked() {
+    model(|| {
+      let a = WaitGroup::new().with_spin_rounds(0);
+      let b = WaitGroup::new().with_spin_rounds(0);
+      let t_a = a.add(1);
+      let t_b = b.add(1);
+      let h = thread::spawn(move || {
+        t_b.done();
+        t_a.done();
+      });
+
+      assert!(wg::wait_any([&a, &b]).is_some());
+      h.join().unwrap();
+    });
+  }
+
+  #[test]
+  fn auto_wait_sees_dropped_clones() {
+    model(|| {
+      let wg = wg::AutoWaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.clone();
+      let t_effects = effects.clone();
+      let h = thread::spawn(move || {
+        t_effects.fetch_add(1, Ordering::Relaxed);
+        drop(t_wg);
+      });
+
+      wg.wait();
+      assert_eq!(effects.load(Ordering::Relaxed), 1);
+      h.join().unwrap();
+    });
+  }
+
+  #[test]
+  fn done_n_batches_units() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(3);
+      let handles = spawn_workers(2, &effects, move |i, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        // One worker reports two units at once.
+        t_wg.done_n(i + 1);
+      });
+
+      wg.wait();
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn forked_token_keeps_wait_blocked() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let root = wg.add_token();
+      let e = effects.clone();
+      let h = thread::spawn(move || {
+        // Work discovered mid-task is handed to another thread while
+        // the main thread may already be waiting.
+        let child = root.fork(1);
+        let ce = e.clone();
+        let inner = thread::spawn(move || {
+          ce.fetch_add(1, Ordering::Relaxed);
+          child.done();
+        });
+        e.fetch_add(1, Ordering::Relaxed);
+        root.done();
+        inner
+      });
+
+      wg.wait();
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      h.join().unwrap().join().unwrap();
+    });
+  }
+
+  #[test]
+  fn add_racing_seal_fails_or_is_waited_for() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.clone();
+      let e = effects.clone();
+      let h = thread::spawn(move || match t_wg.try_add(1) {
+        Ok(t_wg) => {
+          e.fetch_add(1, Ordering::Relaxed);
+          t_wg.done();
+          true
+        }
+        Err(_) => false,
+      });
+
+      wg.seal();
+      wg.wait();
+      let seen = effects.load(Ordering::Relaxed);
+      if h.join().unwrap() {
+        assert_eq!(seen, 1);
+      }
+    });
+  }
+
+  #[test]
+  fn reset_releases_a_blocked_waiter() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      wg.add(1);
+      let t_wg = wg.clone();
+      let h = thread::spawn(move || t_wg.reset());
+
+      // A reset before the call started is reported as `Drained`, so
+      // only check that the waiter is released.
+      wg.wait_outcome();
+      assert_eq!(wg.remaining(), 0);
+      h.join().unwrap();
+    });
+  }
+
+  #[test]
+  fn waiters_drops_back_to_zero() {
+    model(|| {
+      let wg = WaitGroup::new().with_spin_rounds(0);
+      let t_wg = wg.add(1);
+      let w_wg = wg.clone();
+      let h = thread::spawn(move || w_wg.wait());
+
+      assert!(wg.waiters() <= 1);
+      t_wg.done();
+      h.join().unwrap();
+      assert_eq!(wg.waiters(), 0);
+    });
+  }
+
+  #[test]
+  fn phaser_arrive_and_await_sees_all_parties() {
+    model(|| {
+      let phaser = wg::Phaser::new(2);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let p = phaser.clone();
+      let e = effects.clone();
+      let h = thread::spawn(move || {
+        e.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(p.arrive_and_await(), 1);
+      });
+
+      effects.fetch_add(1, Ordering::Relaxed);
+      assert_eq!(phaser.arrive_and_await(), 1);
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      h.join().unwrap();
+    });
+  }
+
+  #[test]
+  fn tiered_phaser_sees_all_parties() {
+    model(|| {
+      let root = wg::Phaser::new(1);
+      let child = root.child(1);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let e = effects.clone();
+      let h = thread::spawn(move || {
+        e.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(child.arrive_and_await(), 1);
+      });
+
+      effects.fetch_add(1, Ordering::Relaxed);
+      assert_eq!(root.arrive_and_await(), 1);
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      h.join().unwrap();
+    });
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod spin {
+  use super::*;
+  use wg::spin::{Spin, WaitGroup, WaitGroupRef};
+
+  #[test]
+  fn wait_sees_all_workers() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      wg.wait_with(Spin::new());
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn borrowed_wait_sees_all_workers() {
+    model(|| {
+      let counter = Arc::new(AtomicUsize::new(2));
+      let effects = Arc::new(AtomicUsize::new(0));
+      let worker_counter = counter.clone();
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        WaitGroupRef::new(&worker_counter).done();
+      });
+
+      WaitGroupRef::new(&counter).wait();
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn add_racing_seal_fails_or_is_waited_for() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.clone();
+      let e = effects.clone();
+      let h = thread::spawn(move || match t_wg.try_add(1) {
+        Ok(t_wg) => {
+          e.fetch_add(1, Ordering::Relaxed);
+          t_wg.done();
+          true
+        }
+        Err(_) => false,
+      });
+
+      wg.seal();
+      wg.wait();
+      let seen = effects.load(Ordering::Relaxed);
+      if h.join().unwrap() {
+        assert_eq!(seen, 1);
+      }
+    });
+  }
+
+  #[test]
+  fn forked_token_keeps_wait_blocked() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let root = wg.add_token();
+      let e = effects.clone();
+      let h = thread::spawn(move || {
+        // Work discovered mid-task is handed to another thread while
+        // the main thread may already be waiting.
+        let child = root.fork(1);
+        let ce = e.clone();
+        let inner = thread::spawn(move || {
+          ce.fetch_add(1, Ordering::Relaxed);
+          child.done();
+        });
+        e.fetch_add(1, Ordering::Relaxed);
+        root.done();
+        inner
+      });
+
+      wg.wait_with(Spin::new());
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      h.join().unwrap().join().unwrap();
+    });
+  }
+}
+
+#[cfg(feature = "future")]
+mod future {
+  use super::*;
+  use wg::future::WaitGroup;
+
+  #[test]
+  fn wait_future_sees_all_workers() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      loom::future::block_on(wg.wait());
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn wait_for_at_most_sees_enough_workers() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      loom::future::block_on(wg.wait_for_at_most(1));
+      assert!(effects.load(Ordering::Relaxed) >= 1);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn wait_any_is_woken() {
+    model(|| {
+      let a = WaitGroup::new();
+      let b = WaitGroup::new();
+      let t_a = a.add(1);
+      let t_b = b.add(1);
+      let h = thread::spawn(move || {
+        t_b.done();
+        t_a.done();
+      });
+
+      assert!(loom::future::block_on(wg::future::wait_any([&a, &b])).is_some());
+      h.join().unwrap();
+    });
+  }
+
+  #[test]
+  fn poll_wait_sees_all_workers() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      let mut wait = wg::future::PollWait::new();
+      loom::future::block_on(core::future::poll_fn(|cx| wg.poll_wait(&mut wait, cx)));
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn wait_blocking_sees_all_workers() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let t_wg = wg.add(2);
+      let handles = spawn_workers(2, &effects, move |_, effects| {
+        effects.fetch_add(1, Ordering::Relaxed);
+        t_wg.done();
+      });
+
+      wg.wait_blocking();
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      for h in handles {
+        h.join().unwrap();
+      }
+    });
+  }
+
+  #[test]
+  fn forked_token_keeps_wait_blocked() {
+    model(|| {
+      let wg = WaitGroup::new();
+      let effects = Arc::new(AtomicUsize::new(0));
+      let root = wg.add_token();
+      let e = effects.clone();
+      let h = thread::spawn(move || {
+        // Work discovered mid-task is handed to another thread while
+        // the main thread may already be waiting.
+        let child = root.fork(1);
+        let ce = e.clone();
+        let inner = thread::spawn(move || {
+          ce.fetch_add(1, Ordering::Relaxed);
+          child.done();
+        });
+        e.fetch_add(1, Ordering::Relaxed);
+        root.done();
+        inner
+      });
+
+      loom::future::block_on(wg.wait());
+      assert_eq!(effects.load(Ordering::Relaxed), 2);
+
+      h.join().unwrap().join().unwrap();
+    });
+  }
+
+  #[cfg(feature = "std")]
+  #[test]
+  fn phaser_await_phase_is_woken() {
+    model(|| {
+      let phaser = wg::future::Phaser::new(1);
+      let effects = Arc::new(AtomicUsize::new(0));
+      let p = phaser.clone();
+      let e = effects.clone();
+      let h = thread::spawn(move || {
+        e.fetch_add(1, Ordering::Relaxed);
+        p.arrive();
+      });
+
+      assert_eq!(loom::future::block_on(phaser.await_phase(0)), 1);
+      assert_eq!(effects.load(Ordering::Relaxed), 1);
+
+      h.join().unwrap();
+    });
+  }
+}
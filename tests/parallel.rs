@@ -0,0 +1,81 @@
+#![cfg(not(loom))]
+
+use std::{
+  collections::HashSet,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+  },
+  thread::ThreadId,
+};
+
+use wg::parallel;
+
+#[test]
+fn for_each_visits_every_item_once() {
+  let seen = Mutex::new(Vec::new());
+  parallel::for_each(0..1000, 7, |n| seen.lock().unwrap().push(n));
+
+  let mut seen = seen.into_inner().unwrap();
+  seen.sort_unstable();
+  assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn for_each_spreads_chunks_over_threads() {
+  let threads = Mutex::new(HashSet::<ThreadId>::new());
+  parallel::for_each(0..4, 4, |_| {
+    threads.lock().unwrap().insert(std::thread::current().id());
+    std::thread::sleep(std::time::Duration::from_millis(10));
+  });
+  assert_eq!(threads.into_inner().unwrap().len(), 4);
+}
+
+#[test]
+fn map_preserves_order() {
+  for threads in [0, 1, 2, 3, 8, 64] {
+    let out = parallel::map(0..100u64, threads, |n| n * n);
+    assert_eq!(out, (0..100u64).map(|n| n * n).collect::<Vec<_>>());
+  }
+}
+
+#[test]
+fn small_and_empty_inputs() {
+  assert!(parallel::map(Vec::<u8>::new(), 4, |n| n).is_empty());
+  assert_eq!(parallel::map([5], 4, |n| n + 1), [6]);
+
+  let calls = AtomicUsize::new(0);
+  parallel::for_each(0..3, 16, |_| {
+    calls.fetch_add(1, Ordering::Relaxed);
+  });
+  assert_eq!(calls.into_inner(), 3);
+}
+
+#[test]
+fn panics_propagate_after_every_chunk_ran() {
+  let calls = AtomicUsize::new(0);
+  let err = std::panic::catch_unwind(|| {
+    parallel::for_each(0..40, 4, |n| {
+      calls.fetch_add(1, Ordering::Relaxed);
+      if n == 5 {
+        panic!("bad item");
+      }
+    })
+  })
+  .unwrap_err();
+
+  assert_eq!(err.downcast_ref::<&str>(), Some(&"bad item"));
+  // Only the rest of the panicking chunk is skipped.
+  assert_eq!(calls.into_inner(), 40 - 4);
+}
+
+#[test]
+#[should_panic(expected = "bad map")]
+fn map_panics_propagate() {
+  parallel::map(0..10, 2, |n| {
+    if n == 9 {
+      panic!("bad map");
+    }
+    n
+  });
+}
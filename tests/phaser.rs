@@ -0,0 +1,226 @@
+#![cfg(not(loom))]
+
+use std::{
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
+
+use wg::Phaser;
+
+#[test]
+fn phases_advance_once_every_party_arrived() {
+  let phaser = Phaser::new(3);
+  assert_eq!((phaser.phase(), phaser.registered()), (0, 3));
+
+  assert_eq!(phaser.arrive(), 0);
+  assert_eq!(phaser.arrive(), 0);
+  assert_eq!((phaser.phase(), phaser.unarrived()), (0, 1));
+  assert_eq!(phaser.arrive(), 0);
+  assert_eq!((phaser.phase(), phaser.unarrived()), (1, 3));
+  // A completed phase is awaited without blocking.
+  assert_eq!(phaser.await_phase(0), 1);
+}
+
+#[test]
+fn arrive_and_await_keeps_parties_in_step() {
+  let phaser = Phaser::new(4);
+  let steps = Arc::new(AtomicUsize::new(0));
+  let mut handles = Vec::new();
+  for _ in 0..4 {
+    let p = phaser.clone();
+    let steps = steps.clone();
+    handles.push(std::thread::spawn(move || {
+      for phase in 0..5 {
+        steps.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(p.arrive_and_await(), phase + 1);
+        // Every party finished the step before anyone moved on.
+        assert!(steps.load(Ordering::Relaxed) >= 4 * (phase as usize + 1));
+      }
+      p.arrive_and_deregister();
+    }));
+  }
+  for h in handles {
+    h.join().unwrap();
+  }
+  assert_eq!(phaser.phase(), 6);
+  assert!(phaser.is_terminated());
+  assert_eq!(phaser.register(), None);
+}
+
+#[test]
+fn parties_join_and_leave_between_phases() {
+  let phaser = Phaser::new(2);
+  phaser.arrive();
+  // A late joiner holds up the current phase.
+  assert_eq!(phaser.register(), Some(0));
+  phaser.arrive();
+  assert_eq!(phaser.phase(), 0);
+  phaser.arrive_and_deregister();
+  assert_eq!((phaser.phase(), phaser.registered()), (1, 2));
+
+  phaser.arrive_and_deregister();
+  phaser.arrive();
+  assert_eq!((phaser.phase(), phaser.registered()), (2, 1));
+}
+
+#[test]
+fn await_phase_from_a_non_party() {
+  let phaser = Phaser::new(1);
+  let p = phaser.clone();
+  let h = std::thread::spawn(move || p.await_phase(0));
+  std::thread::sleep(Duration::from_millis(10));
+  assert!(!h.is_finished());
+  phaser.arrive();
+  assert_eq!(h.join().unwrap(), 1);
+}
+
+#[test]
+fn termination_releases_waiters() {
+  let phaser = Phaser::new(1);
+  let p = phaser.clone();
+  let h = std::thread::spawn(move || p.await_phase(5));
+  phaser.arrive_and_deregister();
+  assert_eq!(h.join().unwrap(), 1);
+  assert!(phaser.is_terminated());
+}
+
+#[test]
+#[should_panic(expected = "exceed its registered parties")]
+fn arriving_too_often_panics() {
+  let phaser = Phaser::new(1);
+  phaser.arrive_and_deregister();
+  phaser.arrive();
+}
+
+#[test]
+fn a_child_arrives_at_its_parent_as_one_party() {
+  let root = Phaser::new(1);
+  let child = root.child(2);
+  assert_eq!((root.registered(), child.registered()), (2, 2));
+
+  child.arrive();
+  assert_eq!(root.unarrived(), 2);
+  child.arrive();
+  assert_eq!(root.unarrived(), 1);
+  root.arrive();
+  assert_eq!((root.phase(), child.phase()), (1, 1));
+  assert_eq!(child.unarrived(), 2);
+
+  // A child without parties leaves its parent.
+  child.arrive_and_deregister();
+  child.arrive_and_deregister();
+  assert_eq!(root.registered(), 1);
+  root.arrive();
+  assert_eq!(root.phase(), 2);
+  assert_eq!(child.register(), Some(2));
+  assert_eq!(root.registered(), 2);
+}
+
+#[test]
+fn late_parties_of_a_child_hold_up_the_phase() {
+  let root = Phaser::new(1);
+  let child = root.child(1);
+  child.arrive();
+  // The child already arrived at the root for phase 0.
+  assert_eq!(child.register(), Some(0));
+  assert_eq!(root.unarrived(), 2);
+  root.arrive();
+  assert_eq!(root.phase(), 0);
+
+  child.arrive();
+  assert_eq!(root.phase(), 1);
+  assert_eq!((child.registered(), child.unarrived()), (2, 2));
+  assert_eq!(root.registered(), 2);
+}
+
+#[test]
+fn tiered_phasers_under_load() {
+  // One party holds the first phase until every leaf is registered.
+  let root = Phaser::new(1);
+  let total = Arc::new(AtomicUsize::new(0));
+  let mut leaves = Vec::new();
+  for _ in 0..4 {
+    let mid = root.child(0);
+    for _ in 0..2 {
+      leaves.push(mid.child(4));
+    }
+  }
+  assert_eq!(root.registered(), 5);
+
+  let mut handles = Vec::new();
+  for leaf in &leaves {
+    for _ in 0..4 {
+      let p = leaf.clone();
+      let total = total.clone();
+      handles.push(std::thread::spawn(move || {
+        for phase in 0..20 {
+          total.fetch_add(1, Ordering::Relaxed);
+          assert_eq!(p.arrive_and_await(), phase + 1);
+          assert!(total.load(Ordering::Relaxed) >= 32 * (phase as usize + 1));
+        }
+        p.arrive_and_deregister();
+      }));
+    }
+  }
+  root.arrive_and_deregister();
+  for h in handles {
+    h.join().unwrap();
+  }
+  assert_eq!(total.load(Ordering::Relaxed), 32 * 20);
+  assert!(root.is_terminated());
+}
+
+#[cfg(feature = "future")]
+mod future {
+  use super::*;
+  use wg::future::Phaser;
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn arrive_and_await_keeps_tasks_in_step() {
+    let root = Phaser::new(0);
+    // Half of the tasks go through a child.
+    let parties: Vec<_> = (0..8)
+      .map(|i| {
+        if i % 2 == 0 {
+          root.register().unwrap();
+          root.clone()
+        } else {
+          root.child(1)
+        }
+      })
+      .collect();
+
+    let steps = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::new();
+    for p in parties {
+      let steps = steps.clone();
+      handles.push(tokio::spawn(async move {
+        for phase in 0..5 {
+          steps.fetch_add(1, Ordering::Relaxed);
+          assert_eq!(p.arrive_and_await().await, phase + 1);
+          assert!(steps.load(Ordering::Relaxed) >= 8 * (phase as usize + 1));
+        }
+        p.arrive_and_deregister();
+      }));
+    }
+    for h in handles {
+      h.await.unwrap();
+    }
+    assert!(root.is_terminated());
+  }
+
+  #[test]
+  fn await_phase_from_a_non_party() {
+    let phaser = Phaser::new(1);
+    let p = phaser.clone();
+    let h = std::thread::spawn(move || smol::block_on(p.await_phase(0)));
+    std::thread::sleep(Duration::from_millis(10));
+    assert!(!h.is_finished());
+    assert_eq!(phaser.arrive(), 0);
+    assert_eq!(h.join().unwrap(), 1);
+    assert_eq!(smol::block_on(phaser.await_phase(0)), 1);
+  }
+}
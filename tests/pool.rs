@@ -0,0 +1,125 @@
+#![cfg(not(loom))]
+
+use std::{
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Barrier,
+  },
+  time::Duration,
+};
+
+use wg::{pool::ThreadPool, WaitGroup};
+
+#[test]
+fn join_waits_for_queued_jobs() {
+  let pool = ThreadPool::new(3);
+  assert_eq!(pool.size(), 3);
+  let ctr = Arc::new(AtomicUsize::new(0));
+
+  for _ in 0..3 {
+    for _ in 0..20 {
+      let ctr = ctr.clone();
+      pool.execute(move || {
+        std::thread::sleep(Duration::from_millis(1));
+        ctr.fetch_add(1, Ordering::Relaxed);
+      });
+    }
+    pool.join().unwrap();
+  }
+  assert_eq!(ctr.load(Ordering::Relaxed), 60);
+  assert_eq!(pool.wait_group().remaining(), 0);
+}
+
+#[test]
+fn a_panicking_job_does_not_kill_its_worker() {
+  let pool = ThreadPool::new(1);
+  let ctr = Arc::new(AtomicUsize::new(0));
+
+  pool.execute(|| panic!("bad job"));
+  for _ in 0..4 {
+    let ctr = ctr.clone();
+    pool.execute(move || {
+      ctr.fetch_add(1, Ordering::Relaxed);
+    });
+  }
+
+  let err = pool.join().unwrap_err();
+  assert_eq!(err.message(), Some("bad job"));
+  // The single worker survived and ran everything queued after it.
+  assert_eq!(ctr.load(Ordering::Relaxed), 4);
+  pool.execute(|| ());
+  pool.join().unwrap();
+}
+
+#[test]
+fn builder_names_threads_and_uses_the_callers_group() {
+  let wg = WaitGroup::new();
+  let pool = ThreadPool::builder()
+    .size(2)
+    .thread_name("tester")
+    .queue_capacity(1)
+    .wait_group(wg.clone())
+    .build()
+    .unwrap();
+
+  let names = Arc::new(std::sync::Mutex::new(Vec::new()));
+  for _ in 0..8 {
+    let names = names.clone();
+    pool.execute(move || {
+      let name = std::thread::current().name().unwrap().to_owned();
+      names.lock().unwrap().push(name);
+    });
+  }
+
+  wg.wait();
+  let names = names.lock().unwrap();
+  assert_eq!(names.len(), 8);
+  assert!(names.iter().all(|n| n == "tester-0" || n == "tester-1"));
+}
+
+#[test]
+fn bounded_queue_applies_backpressure() {
+  let pool = ThreadPool::builder()
+    .size(1)
+    .queue_capacity(0)
+    .build()
+    .unwrap();
+  let barrier = Arc::new(Barrier::new(2));
+
+  let b = barrier.clone();
+  pool.execute(move || {
+    b.wait();
+  });
+  // With a rendezvous queue, this only returns once the worker has taken
+  // the job, i.e. after the barrier below released the first one.
+  let started = Arc::new(AtomicUsize::new(0));
+  let s = started.clone();
+  let h = std::thread::spawn(move || {
+    barrier.wait();
+    s.fetch_add(1, Ordering::Relaxed);
+  });
+  pool.execute(|| ());
+  pool.join().unwrap();
+  h.join().unwrap();
+  assert_eq!(started.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn drop_finishes_queued_jobs() {
+  let ctr = Arc::new(AtomicUsize::new(0));
+  {
+    let pool = ThreadPool::new(2);
+    for _ in 0..10 {
+      let ctr = ctr.clone();
+      pool.execute(move || {
+        ctr.fetch_add(1, Ordering::Relaxed);
+      });
+    }
+  }
+  assert_eq!(ctr.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn zero_size_is_an_error() {
+  assert!(ThreadPool::builder().size(0).build().is_err());
+}
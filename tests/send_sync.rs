@@ -13,10 +13,53 @@ const fn _assert_send_sync<T: Send + Sync>() {}
 fn waitgroup_is_send_sync() {
   #[cfg(feature = "std")]
   _assert_send_sync::<wg::WaitGroup>();
+  #[cfg(all(
+    feature = "std",
+    any(feature = "portable-atomic", not(feature = "triomphe"))
+  ))]
+  _assert_send_sync::<wg::WeakWaitGroup>();
+  #[cfg(feature = "std")]
+  _assert_send_sync::<wg::AutoWaitGroup>();
+  #[cfg(feature = "std")]
+  _assert_send_sync::<wg::Token>();
+  #[cfg(feature = "std")]
+  _assert_send_sync::<wg::Phaser>();
+  #[cfg(all(feature = "std", not(loom)))]
+  _assert_send_sync::<wg::pool::ThreadPool>();
   #[cfg(any(feature = "alloc", feature = "std"))]
   _assert_send_sync::<wg::spin::WaitGroup>();
+  #[cfg(all(
+    any(feature = "alloc", feature = "std"),
+    any(feature = "portable-atomic", not(feature = "triomphe"))
+  ))]
+  _assert_send_sync::<wg::spin::WeakWaitGroup>();
+  #[cfg(any(feature = "alloc", feature = "std"))]
+  _assert_send_sync::<wg::spin::AutoWaitGroup>();
+  #[cfg(any(feature = "alloc", feature = "std"))]
+  _assert_send_sync::<wg::spin::Token>();
+  _assert_send_sync::<wg::spin::WaitGroupRef<'static>>();
+  #[cfg(feature = "atomic-waker")]
+  _assert_send_sync::<wg::future::StaticWaitGroup>();
+  #[cfg(feature = "critical-section")]
+  _assert_send_sync::<wg::cs::WaitGroup>();
   #[cfg(feature = "future")]
   {
     _assert_send_sync::<wg::future::WaitGroup>();
+    #[cfg(any(feature = "portable-atomic", not(feature = "triomphe")))]
+    _assert_send_sync::<wg::future::WeakWaitGroup>();
+    _assert_send_sync::<wg::future::AutoWaitGroup>();
+    _assert_send_sync::<wg::future::PollWait>();
+    _assert_send_sync::<wg::future::Token>();
+    _assert_send_sync::<wg::future::WaitOutcomeFuture<'static>>();
+  }
+  #[cfg(all(feature = "std", feature = "future"))]
+  {
+    _assert_send_sync::<wg::future::Phaser>();
+    _assert_send_sync::<wg::future::AwaitPhase<'static>>();
+  }
+  #[cfg(all(feature = "std", feature = "future", not(loom)))]
+  {
+    _assert_send_sync::<wg::shutdown::Shutdown>();
+    _assert_send_sync::<wg::shutdown::ShutdownGuard>();
   }
 }
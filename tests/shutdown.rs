@@ -0,0 +1,138 @@
+#![cfg(not(loom))]
+
+use std::{
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+  thread,
+  time::Duration,
+};
+
+use wg::shutdown::{Outcome, Shutdown};
+
+#[test]
+fn drain_blocking_waits_for_guards() {
+  let shutdown = Shutdown::new().with_escalation(|| panic!("should not escalate"));
+  let done = Arc::new(AtomicUsize::new(0));
+
+  let handles: Vec<_> = (0..4)
+    .map(|_| {
+      let guard = shutdown.register();
+      let done = done.clone();
+      thread::spawn(move || {
+        while !guard.is_triggered() {
+          thread::sleep(Duration::from_millis(1));
+        }
+        done.fetch_add(1, Ordering::Relaxed);
+      })
+    })
+    .collect();
+
+  assert_eq!(shutdown.remaining(), 4);
+  assert_eq!(
+    shutdown.drain_blocking(Duration::from_secs(10)),
+    Outcome::Drained
+  );
+  assert_eq!(done.load(Ordering::Relaxed), 4);
+  assert_eq!(shutdown.remaining(), 0);
+
+  for h in handles {
+    h.join().unwrap();
+  }
+}
+
+#[test]
+fn escalation_runs_once_after_grace() {
+  let calls = Arc::new(AtomicUsize::new(0));
+  let c = calls.clone();
+  let shutdown = Shutdown::new().with_escalation(move || {
+    c.fetch_add(1, Ordering::Relaxed);
+  });
+  let guard = shutdown.register();
+
+  assert_eq!(
+    shutdown.drain_blocking(Duration::from_millis(20)),
+    Outcome::Escalated { remaining: 1 }
+  );
+  assert_eq!(
+    shutdown.drain_blocking(Duration::from_millis(1)),
+    Outcome::Escalated { remaining: 1 }
+  );
+  assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+  drop(guard);
+  assert_eq!(shutdown.drain_blocking(Duration::ZERO), Outcome::Drained);
+}
+
+#[test]
+fn wait_triggered_is_woken() {
+  let shutdown = Shutdown::new();
+  assert!(!shutdown.is_triggered());
+
+  let t = shutdown.clone();
+  let h = thread::spawn(move || {
+    thread::sleep(Duration::from_millis(10));
+    t.trigger();
+  });
+
+  shutdown.wait_triggered();
+  assert!(shutdown.is_triggered());
+  h.join().unwrap();
+}
+
+#[tokio::test]
+async fn drain_async() {
+  let shutdown = Shutdown::new();
+
+  for _ in 0..4 {
+    let guard = shutdown.register();
+    tokio::spawn(async move {
+      guard.triggered().await;
+      tokio::time::sleep(Duration::from_millis(5)).await;
+    });
+  }
+
+  let outcome = shutdown
+    .drain(tokio::time::sleep(Duration::from_secs(10)))
+    .await;
+  assert_eq!(outcome, Outcome::Drained);
+}
+
+#[tokio::test]
+async fn drain_async_escalates() {
+  let escalated = Arc::new(AtomicUsize::new(0));
+  let e = escalated.clone();
+  let shutdown = Shutdown::new().with_escalation(move || {
+    e.fetch_add(1, Ordering::Relaxed);
+  });
+
+  let guard = shutdown.register();
+  let outcome = shutdown
+    .drain(tokio::time::sleep(Duration::from_millis(20)))
+    .await;
+  assert_eq!(outcome, Outcome::Escalated { remaining: 1 });
+  assert_eq!(escalated.load(Ordering::Relaxed), 1);
+  assert!(guard.is_triggered());
+}
+
+#[test]
+fn triggered_future_with_smol() {
+  let shutdown = Shutdown::new();
+  let t = shutdown.clone();
+  let h = thread::spawn(move || t.trigger());
+
+  smol::block_on(shutdown.triggered());
+  h.join().unwrap();
+}
+
+#[cfg(all(unix, feature = "signal"))]
+#[test]
+fn sigterm_fires_the_trigger() {
+  let shutdown = Shutdown::new();
+  shutdown.trigger_on_signals().unwrap();
+
+  signal_hook::low_level::raise(signal_hook::consts::SIGTERM).unwrap();
+  shutdown.wait_triggered();
+  assert!(shutdown.is_triggered());
+}
@@ -1,6 +1,8 @@
-#![cfg(any(feature = "std", feature = "alloc"))]
+use wg::spin::AtomicUsize;
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 use wg::spin::WaitGroup;
+use wg::spin::WaitGroupRef;
 
 #[cfg(feature = "std")]
 extern crate std;
@@ -9,13 +11,9 @@ extern crate std;
 extern crate alloc as std;
 
 #[cfg(feature = "std")]
-use std::{
-  sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
-  },
-  time::Duration,
-};
+use core::sync::atomic::Ordering;
+#[cfg(feature = "std")]
+use std::{sync::Arc, time::Duration};
 
 #[cfg(feature = "std")]
 #[test]
@@ -69,6 +67,7 @@ fn from_count() {
   assert_eq!(wg.remaining(), 0);
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 #[test]
 fn done_returns_remaining() {
   let wg = WaitGroup::from(3);
@@ -79,6 +78,35 @@ fn done_returns_remaining() {
   assert_eq!(wg.done(), 0);
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn done_n_saturates() {
+  let mut wg = WaitGroup::from(10);
+  assert_eq!(wg.done_n(3), 7);
+  wg -= 2;
+  assert_eq!(wg.remaining(), 5);
+  assert_eq!(wg.done_n(100), 0);
+  assert_eq!(wg.done_n(1), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn wait_for_at_most() {
+  let wg = WaitGroup::from(4);
+  let t_wg = wg.clone();
+  std::thread::spawn(move || {
+    for _ in 0..4 {
+      std::thread::sleep(Duration::from_millis(2));
+      t_wg.done();
+    }
+  });
+  wg.wait_for_at_most(2);
+  assert!(wg.remaining() <= 2);
+  wg.wait_for_at_most(0);
+  assert_eq!(wg.remaining(), 0);
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
 #[test]
 fn debug_and_clone() {
   let wg = WaitGroup::new();
@@ -97,3 +125,254 @@ fn wait_on_zero_returns_immediately() {
   wg.wait();
   assert!(start.elapsed() < Duration::from_millis(50));
 }
+
+// --------------------------------------------------------------------
+// `WaitGroupRef` — borrowed, allocation-free variant. These run under
+// every feature combination, including pure `core`.
+// --------------------------------------------------------------------
+
+#[test]
+fn ref_done_returns_remaining() {
+  let counter = AtomicUsize::new(3);
+  let wg = WaitGroupRef::new(&counter);
+  assert_eq!(wg.remaining(), 3);
+  assert_eq!(wg.done(), 2);
+  assert_eq!(wg.add(2).done(), 3);
+  assert_eq!(wg.done(), 2);
+  assert_eq!(wg.done(), 1);
+  assert_eq!(wg.done(), 0);
+  // Over-done is a silent no-op, returns 0.
+  assert_eq!(wg.done(), 0);
+  wg.wait();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn ref_static_counter() {
+  static COUNTER: AtomicUsize = AtomicUsize::new(0);
+  const WG: WaitGroupRef<'static> = WaitGroupRef::new(&COUNTER);
+
+  let t_wg = WG.add(4);
+  std::thread::scope(|s| {
+    for _ in 0..4 {
+      s.spawn(move || {
+        t_wg.done();
+      });
+    }
+    WG.wait();
+  });
+  assert_eq!(WG.remaining(), 0);
+}
+
+#[test]
+fn ref_done_n_saturates() {
+  let counter = AtomicUsize::new(4);
+  let wg = WaitGroupRef::new(&counter);
+  assert_eq!(wg.done_n(3), 1);
+  assert_eq!(wg.done_n(3), 0);
+  assert_eq!(wg.done_n(3), 0);
+}
+
+#[test]
+fn ref_wait_for_at_most_satisfied() {
+  let counter = AtomicUsize::new(3);
+  let wg = WaitGroupRef::new(&counter);
+  wg.wait_for_at_most(3);
+  wg.done_n(2);
+  wg.wait_for_at_most(1);
+}
+
+#[test]
+fn ref_shares_counter() {
+  let counter = AtomicUsize::new(0);
+  let a = WaitGroupRef::new(&counter);
+  let b = WaitGroupRef::from(&counter);
+  a.add(2);
+  assert_eq!(b.remaining(), 2);
+  assert_eq!(format!("{:?}", a), format!("{:?}", b));
+}
+
+// --------------------------------------------------------------------
+// Pluggable relax strategies.
+// --------------------------------------------------------------------
+
+#[test]
+fn ref_wait_with_custom_relax() {
+  let counter = AtomicUsize::new(3);
+  let wg = WaitGroupRef::new(&counter);
+  let mut calls = 0;
+  // The hook completes one unit per call, so the wait ends after
+  // exactly three relax rounds.
+  wg.wait_with(|| {
+    calls += 1;
+    wg.done();
+  });
+  assert_eq!(calls, 3);
+  assert_eq!(wg.remaining(), 0);
+}
+
+#[test]
+fn ref_wait_with_on_zero_never_relaxes() {
+  let counter = AtomicUsize::new(0);
+  let wg = WaitGroupRef::new(&counter);
+  wg.wait_with(|| panic!("relax must not run on a zero counter"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn wait_with_builtin_strategies() {
+  use wg::spin::{Spin, SpinThenSleep, SpinThenYield};
+
+  fn round(wait: impl FnOnce(&WaitGroup)) {
+    let wg = WaitGroup::new();
+    let t_wg = wg.add(1);
+    std::thread::spawn(move || {
+      std::thread::sleep(Duration::from_millis(5));
+      t_wg.done();
+    });
+    wait(&wg);
+    assert_eq!(wg.remaining(), 0);
+  }
+
+  round(|wg| wg.wait_with(Spin::new()));
+  round(|wg| wg.wait_with(SpinThenYield::new()));
+  round(|wg| wg.wait_with(SpinThenSleep::new(Duration::from_millis(1))));
+}
+
+#[cfg(all(
+  feature = "std",
+  any(feature = "portable-atomic", not(feature = "triomphe"))
+))]
+#[test]
+fn weak_wait_returns_once_orphaned() {
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(2);
+  let weak = wg.downgrade();
+  assert_eq!(weak.upgrade().unwrap().done(), 1);
+  drop(wg);
+
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(5));
+    drop(t_wg);
+  });
+  weak.wait();
+  assert_eq!(weak.remaining(), 0);
+  assert!(weak.upgrade().is_none());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn auto_wait_group() {
+  let wg = wg::spin::AutoWaitGroup::new();
+  let ctr = Arc::new(AtomicUsize::new(0));
+  for _ in 0..5 {
+    let wg = wg.clone();
+    let ctrx = ctr.clone();
+    std::thread::spawn(move || {
+      ctrx.fetch_add(1, Ordering::Relaxed);
+      drop(wg);
+    });
+  }
+  wg.wait();
+  assert_eq!(ctr.load(Ordering::Relaxed), 5);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn auto_wait_group_counts_waiters() {
+  let wg = wg::spin::AutoWaitGroup::new();
+  let participant = wg.clone();
+  let waiter = std::thread::spawn(move || wg.wait());
+  while participant.waiters() == 0 {
+    std::thread::yield_now();
+  }
+  drop(participant);
+  waiter.join().unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn tokens() {
+  let wg = WaitGroup::new();
+  let (a, b) = wg.add_tokens(3).split(2);
+  std::thread::spawn(move || a.done());
+  let t = wg.add_token();
+  std::thread::spawn(move || drop(t));
+  assert!(b.done() <= 3);
+  wg.wait();
+  assert_eq!(wg.remaining(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn forked_tokens_track_a_growing_tree() {
+  use wg::spin::Token;
+
+  fn visit(depth: u32, token: Token, seen: Arc<AtomicUsize>) {
+    seen.fetch_add(1, Ordering::Relaxed);
+    if depth > 0 {
+      for _ in 0..2 {
+        let child = token.fork(1);
+        let seen = seen.clone();
+        std::thread::spawn(move || visit(depth - 1, child, seen));
+      }
+    }
+    token.done();
+  }
+
+  let wg = WaitGroup::new();
+  let seen = Arc::new(AtomicUsize::new(0));
+  let root = wg.add_token();
+  let s = seen.clone();
+  std::thread::spawn(move || visit(4, root, s));
+
+  wg.wait();
+  assert_eq!(seen.load(Ordering::Relaxed), 31);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn seal_and_reset() {
+  use wg::{Sealed, WaitOutcome};
+
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(1);
+  wg.seal();
+  assert!(wg.is_sealed());
+  assert_eq!(wg.try_add(1).unwrap_err(), Sealed);
+  assert_eq!(wg.remaining(), 1);
+
+  let waiter = {
+    let wg = wg.clone();
+    std::thread::spawn(move || wg.wait_outcome())
+  };
+  while wg.waiters() == 0 {
+    std::thread::yield_now();
+  }
+  wg.reset();
+  assert_eq!(waiter.join().unwrap(), WaitOutcome::Reset);
+  drop(t_wg);
+  assert_eq!(wg.wait_outcome(), WaitOutcome::Drained);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn waiters_counts_spinning_threads() {
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(1);
+  let waiters: std::vec::Vec<_> = (0..3)
+    .map(|_| {
+      let wg = wg.clone();
+      std::thread::spawn(move || wg.wait())
+    })
+    .collect();
+
+  while wg.waiters() < 3 {
+    std::thread::yield_now();
+  }
+  t_wg.done();
+  for w in waiters {
+    w.join().unwrap();
+  }
+  assert_eq!(wg.waiters(), 0);
+}
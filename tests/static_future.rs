@@ -0,0 +1,208 @@
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+  task::{Context, Wake, Waker},
+  time::Duration,
+};
+
+use wg::future::StaticWaitGroup;
+
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+  fn wake(self: Arc<Self>) {
+    self.0.fetch_add(1, Ordering::SeqCst);
+  }
+
+  fn wake_by_ref(self: &Arc<Self>) {
+    self.0.fetch_add(1, Ordering::SeqCst);
+  }
+}
+
+fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+  let w = Arc::new(CountingWaker(AtomicUsize::new(0)));
+  (w.clone(), Waker::from(w))
+}
+
+#[tokio::test]
+async fn static_group_tokio() {
+  static WG: StaticWaitGroup = StaticWaitGroup::new();
+  static CTR: AtomicUsize = AtomicUsize::new(0);
+
+  for _ in 0..5 {
+    WG.add(1);
+    tokio::spawn(async {
+      tokio::time::sleep(Duration::from_millis(10)).await;
+      CTR.fetch_add(1, Ordering::Relaxed);
+      WG.done();
+    });
+  }
+
+  WG.wait().await;
+  assert_eq!(CTR.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn static_group_smol() {
+  static WG: StaticWaitGroup<2> = StaticWaitGroup::new();
+
+  smol::block_on(async {
+    WG.add(3);
+    for _ in 0..3 {
+      smol::spawn(async {
+        smol::Timer::after(Duration::from_millis(10)).await;
+        WG.done();
+      })
+      .detach();
+    }
+    WG.wait().await;
+    assert_eq!(WG.remaining(), 0);
+  });
+}
+
+#[tokio::test]
+async fn more_waiters_than_slots() {
+  let wg: Arc<StaticWaitGroup<1>> = Arc::new(StaticWaitGroup::from(1));
+
+  let waiters: Vec<_> = (0..4)
+    .map(|_| {
+      let wg = wg.clone();
+      tokio::spawn(async move { wg.wait().await })
+    })
+    .collect();
+
+  tokio::time::sleep(Duration::from_millis(10)).await;
+  wg.done();
+  for w in waiters {
+    w.await.unwrap();
+  }
+}
+
+#[test]
+fn done_wakes_registered_waiter_once() {
+  let wg: StaticWaitGroup = StaticWaitGroup::from(2);
+  let (count, waker) = counting_waker();
+  let mut cx = Context::from_waker(&waker);
+
+  let mut fut = wg.wait();
+  assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+
+  // Decrements that leave the counter non-zero do not wake.
+  assert_eq!(wg.done(), 1);
+  assert_eq!(count.0.load(Ordering::SeqCst), 0);
+
+  assert_eq!(wg.done(), 0);
+  assert_eq!(count.0.load(Ordering::SeqCst), 1);
+  assert!(Pin::new(&mut fut).poll(&mut cx).is_ready());
+}
+
+#[test]
+fn full_slots_reschedule_the_extra_waiter() {
+  let wg: StaticWaitGroup<1> = StaticWaitGroup::from(1);
+  let (first_count, first_waker) = counting_waker();
+  let (extra_count, extra_waker) = counting_waker();
+
+  let mut first = wg.wait();
+  let mut extra = wg.wait();
+  assert!(Pin::new(&mut first)
+    .poll(&mut Context::from_waker(&first_waker))
+    .is_pending());
+  assert!(Pin::new(&mut extra)
+    .poll(&mut Context::from_waker(&extra_waker))
+    .is_pending());
+
+  // The slot-less waiter asked to be polled again instead of sleeping.
+  assert_eq!(extra_count.0.load(Ordering::SeqCst), 1);
+  assert_eq!(first_count.0.load(Ordering::SeqCst), 0);
+
+  // Dropping the first future frees its slot for the extra waiter.
+  drop(first);
+  assert!(Pin::new(&mut extra)
+    .poll(&mut Context::from_waker(&extra_waker))
+    .is_pending());
+  assert_eq!(extra_count.0.load(Ordering::SeqCst), 1);
+
+  wg.done();
+  assert_eq!(extra_count.0.load(Ordering::SeqCst), 2);
+  assert!(Pin::new(&mut extra)
+    .poll(&mut Context::from_waker(&extra_waker))
+    .is_ready());
+}
+
+#[test]
+fn waiters_counts_futures_holding_a_slot() {
+  let wg: StaticWaitGroup<1> = StaticWaitGroup::from(1);
+  let (_, waker) = counting_waker();
+  let mut cx = Context::from_waker(&waker);
+
+  // Created but never polled: no slot yet.
+  let mut first = wg.wait();
+  let mut extra = wg.wait();
+  assert_eq!(wg.waiters(), 0);
+  assert!(Pin::new(&mut first).poll(&mut cx).is_pending());
+  // The slot-less waiter is not counted.
+  assert!(Pin::new(&mut extra).poll(&mut cx).is_pending());
+  assert_eq!(wg.waiters(), 1);
+
+  drop(first);
+  assert_eq!(wg.waiters(), 0);
+  assert!(Pin::new(&mut extra).poll(&mut cx).is_pending());
+  assert_eq!(wg.waiters(), 1);
+
+  wg.done();
+  assert!(Pin::new(&mut extra).poll(&mut cx).is_ready());
+  assert_eq!(wg.waiters(), 0);
+}
+
+#[test]
+fn done_n_wakes_once_and_saturates() {
+  let wg: StaticWaitGroup = StaticWaitGroup::from(5);
+  let (count, waker) = counting_waker();
+  let mut cx = Context::from_waker(&waker);
+
+  let mut fut = wg.wait();
+  assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+  assert_eq!(wg.done_n(2), 3);
+  assert_eq!(count.0.load(Ordering::SeqCst), 0);
+  assert_eq!(wg.done_n(10), 0);
+  assert_eq!(count.0.load(Ordering::SeqCst), 1);
+  assert_eq!(wg.done_n(10), 0);
+  assert_eq!(count.0.load(Ordering::SeqCst), 1);
+  assert!(Pin::new(&mut fut).poll(&mut cx).is_ready());
+}
+
+#[test]
+fn wait_on_zero_is_ready() {
+  let wg: StaticWaitGroup = StaticWaitGroup::new();
+  let (count, waker) = counting_waker();
+  assert!(Pin::new(&mut wg.wait())
+    .poll(&mut Context::from_waker(&waker))
+    .is_ready());
+  assert_eq!(count.0.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn counter_ops() {
+  let wg: StaticWaitGroup = StaticWaitGroup::new();
+  assert_eq!(wg.add(2).add(1).remaining(), 3);
+  assert_eq!(wg.done(), 2);
+  assert_eq!(wg.done(), 1);
+  assert_eq!(wg.done(), 0);
+  // Over-done is a silent no-op, returns 0.
+  assert_eq!(wg.done(), 0);
+  assert_eq!(
+    format!("{wg:?}"),
+    "StaticWaitGroup { counter: 0, slots: 4 }"
+  );
+}
+
+#[test]
+#[should_panic(expected = "WaitGroup counter overflow")]
+fn add_overflow_panics() {
+  let wg: StaticWaitGroup = StaticWaitGroup::from(usize::MAX);
+  wg.add(1);
+}
@@ -117,3 +117,510 @@ fn test_add_assign() {
   wg += 2;
   assert_eq!(wg.remaining(), 5);
 }
+
+/// `done_n` decrements in one step and saturates at zero.
+#[test]
+fn test_done_n() {
+  let mut wg = WaitGroup::from(10);
+  assert_eq!(wg.done_n(3), 7);
+  assert_eq!(wg.done_n(0), 7);
+  wg -= 2;
+  assert_eq!(wg.remaining(), 5);
+  // Underflow saturates instead of wrapping or panicking.
+  assert_eq!(wg.done_n(100), 0);
+  assert_eq!(wg.done_n(1), 0);
+  assert_eq!(wg.remaining(), 0);
+}
+
+/// One `done_n` from a worker releases every waiter.
+#[test]
+fn test_done_n_releases_waiters() {
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(1000);
+  let waiters: Vec<_> = (0..4)
+    .map(|_| {
+      let wg = wg.clone();
+      std::thread::spawn(move || wg.wait())
+    })
+    .collect();
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(10));
+    t_wg.done_n(1000);
+  });
+  wg.wait();
+  for w in waiters {
+    w.join().unwrap();
+  }
+}
+
+/// Threshold waiters return once the counter is at or below their own
+/// threshold, even when a larger threshold is reached first.
+#[test]
+fn test_wait_for_at_most() {
+  let wg = WaitGroup::new().with_spin_rounds(0);
+  let t_wg = wg.add(10);
+
+  let waiters: Vec<_> = [7, 3, 0]
+    .into_iter()
+    .map(|n| {
+      let wg = wg.clone();
+      std::thread::spawn(move || {
+        wg.wait_for_at_most(n);
+        assert!(wg.remaining() <= n);
+      })
+    })
+    .collect();
+
+  for _ in 0..10 {
+    std::thread::sleep(Duration::from_millis(2));
+    t_wg.done();
+  }
+  for w in waiters {
+    w.join().unwrap();
+  }
+
+  // Already satisfied: returns without blocking.
+  wg.wait_for_at_most(5);
+}
+
+/// `wait_all` returns once every group has drained.
+#[test]
+fn test_wait_all() {
+  let groups: Vec<WaitGroup> = (0..3).map(|_| WaitGroup::new()).collect();
+  let ctr = Arc::new(AtomicUsize::new(0));
+  for (i, wg) in groups.iter().enumerate() {
+    let t_wg = wg.add(1);
+    let ctr = ctr.clone();
+    std::thread::spawn(move || {
+      std::thread::sleep(Duration::from_millis(5 * i as u64));
+      ctr.fetch_add(1, Ordering::Relaxed);
+      t_wg.done();
+    });
+  }
+
+  wg::wait_all(&groups);
+  assert_eq!(ctr.load(Ordering::Relaxed), 3);
+  wg::wait_all([]);
+}
+
+/// `wait_any` reports the group that drained, and unregisters from the
+/// others so it can be called again.
+#[test]
+fn test_wait_any() {
+  assert_eq!(wg::wait_any([]), None);
+
+  let slow = WaitGroup::new().with_spin_rounds(0);
+  let fast = WaitGroup::new().with_spin_rounds(0);
+  let t_slow = slow.add(1);
+  let t_fast = fast.add(1);
+  let (tx, rx) = std::sync::mpsc::channel::<()>();
+  std::thread::spawn(move || {
+    rx.recv().unwrap();
+    t_slow.done();
+  });
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(10));
+    t_fast.done();
+  });
+
+  assert_eq!(wg::wait_any([&slow, &fast]), Some(1));
+  // Already drained: fast path.
+  assert_eq!(wg::wait_any([&slow, &fast]), Some(1));
+
+  tx.send(()).unwrap();
+  assert_eq!(wg::wait_any([&slow]), Some(0));
+  assert_eq!(wg::wait_any([&slow, &fast]), Some(0));
+}
+
+/// Many threads waiting while many others complete: every waiter must be
+/// released exactly when the counter reaches zero, under either backend.
+#[test]
+fn test_many_waiters_many_workers() {
+  const WORKERS: usize = 64;
+  const WAITERS: usize = 8;
+
+  for _ in 0..20 {
+    let wg = WaitGroup::from(WORKERS);
+    let finished = Arc::new(AtomicUsize::new(0));
+    std::thread::scope(|s| {
+      for _ in 0..WAITERS {
+        let wg = wg.clone();
+        let finished = finished.clone();
+        s.spawn(move || {
+          wg.wait();
+          assert_eq!(finished.load(Ordering::SeqCst), WORKERS);
+          assert_eq!(wg.remaining(), 0);
+        });
+      }
+      for _ in 0..WORKERS {
+        let wg = wg.clone();
+        let finished = finished.clone();
+        s.spawn(move || {
+          finished.fetch_add(1, Ordering::SeqCst);
+          wg.done();
+        });
+      }
+    });
+  }
+}
+
+#[test]
+fn test_spin_rounds_per_handle() {
+  let mut wg = WaitGroup::new();
+  let default = wg.spin_rounds();
+  assert!(default > 0);
+
+  wg.set_spin_rounds(0);
+  assert_eq!(wg.clone().spin_rounds(), 0);
+  assert_eq!(wg.add(1).spin_rounds(), 0);
+
+  // Other handles keep their own setting.
+  let busy = wg.clone().with_spin_rounds(1_000);
+  assert_eq!(busy.spin_rounds(), 1_000);
+  assert_eq!(wg.spin_rounds(), 0);
+
+  std::thread::scope(|s| {
+    s.spawn(|| {
+      std::thread::sleep(Duration::from_millis(5));
+      wg.done();
+    });
+    busy.wait();
+  });
+  assert_eq!(wg.remaining(), 0);
+}
+
+#[test]
+fn test_wait_without_spin_phase() {
+  let wg = WaitGroup::new().with_spin_rounds(0);
+  let t_wg = wg.add(1);
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(5));
+    t_wg.done();
+  });
+  wg.wait();
+  assert_eq!(wg.remaining(), 0);
+}
+
+#[test]
+fn test_join_reports_worker_panic() {
+  let wg = WaitGroup::new();
+  let ctr = Arc::new(AtomicUsize::new(0));
+  for i in 0..4 {
+    let t_wg = wg.add(1);
+    let ctrx = ctr.clone();
+    std::thread::spawn(move || {
+      let out = t_wg.run(|| {
+        if i == 2 {
+          panic!("worker {i} failed");
+        }
+        ctrx.fetch_add(1, Ordering::Relaxed);
+        i
+      });
+      assert_eq!(out.is_none(), i == 2);
+    });
+  }
+
+  let err = wg.join().unwrap_err();
+  assert_eq!(err.message(), Some("worker 2 failed"));
+  assert_eq!(
+    err.to_string(),
+    "a WaitGroup worker panicked: worker 2 failed"
+  );
+  assert_eq!(ctr.load(Ordering::Relaxed), 3);
+  assert_eq!(wg.remaining(), 0);
+
+  // The payload has been handed over; the group is usable again.
+  assert!(wg.join().is_ok());
+  let t_wg = wg.add(1);
+  std::thread::spawn(move || t_wg.run(|| ()));
+  assert!(wg.join().is_ok());
+}
+
+#[test]
+fn test_join_resumes_worker_panic() {
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(1);
+  std::thread::spawn(move || t_wg.run(|| std::panic::panic_any(42u32)));
+
+  let err = wg.join().unwrap_err();
+  assert_eq!(err.message(), None);
+  let payload =
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| err.resume())).unwrap_err();
+  assert_eq!(payload.downcast_ref::<u32>(), Some(&42));
+}
+
+#[cfg(any(feature = "portable-atomic", not(feature = "triomphe")))]
+#[test]
+fn test_weak_wait_group() {
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(2);
+  let weak = wg.downgrade();
+  assert_eq!(weak.remaining(), 2);
+
+  // Upgrading gives a working handle while the group is alive.
+  let up = weak.upgrade().unwrap();
+  up.done();
+  drop(up);
+  drop(wg);
+  assert_eq!(weak.remaining(), 1);
+
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(5));
+    t_wg.done();
+  });
+  weak.wait();
+  assert_eq!(weak.remaining(), 0);
+}
+
+#[cfg(any(feature = "portable-atomic", not(feature = "triomphe")))]
+#[test]
+fn test_weak_wait_returns_once_orphaned() {
+  let wg = WaitGroup::new().with_spin_rounds(0);
+  let t_wg = wg.add(1);
+  let weak = wg.downgrade();
+  drop(wg);
+
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(5));
+    // Gone without calling `done`.
+    drop(t_wg);
+  });
+  weak.wait();
+  assert_eq!(weak.remaining(), 0);
+  assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_auto_wait_group() {
+  let wg = wg::AutoWaitGroup::new();
+  let ctr = Arc::new(AtomicUsize::new(0));
+  for _ in 0..5 {
+    let wg = wg.clone();
+    let ctrx = ctr.clone();
+    std::thread::spawn(move || {
+      std::thread::sleep(Duration::from_millis(5));
+      ctrx.fetch_add(1, Ordering::Relaxed);
+      drop(wg);
+    });
+  }
+  assert!(wg.participants() >= 1);
+  wg.wait();
+  assert_eq!(ctr.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn test_auto_wait_group_counts_panicking_participants() {
+  let wg = wg::AutoWaitGroup::new();
+  let t_wg = wg.clone();
+  let h = std::thread::spawn(move || {
+    let _wg = t_wg;
+    panic!("participant failed");
+  });
+  // The unwinding thread drops its handle, so this doesn't hang.
+  wg.wait();
+  assert!(h.join().is_err());
+}
+
+#[test]
+fn test_waiters_counts_wait_any_and_auto_waits() {
+  let a = WaitGroup::new();
+  let b = WaitGroup::new();
+  let t_a = a.add(1);
+  let _t_b = b.add(1);
+  let any = {
+    let (a, b) = (a.clone(), b.clone());
+    std::thread::spawn(move || wg::wait_any([&a, &b]))
+  };
+  // `wait_any` counts on every group it watches.
+  while a.waiters() == 0 || b.waiters() == 0 {
+    std::thread::yield_now();
+  }
+  t_a.done();
+  assert_eq!(any.join().unwrap(), Some(0));
+  assert_eq!((a.waiters(), b.waiters()), (0, 0));
+
+  let wg = wg::AutoWaitGroup::new();
+  let participant = wg.clone();
+  let waiter = std::thread::spawn(move || wg.wait());
+  while participant.waiters() == 0 {
+    std::thread::yield_now();
+  }
+  drop(participant);
+  waiter.join().unwrap();
+}
+
+#[test]
+fn test_tokens() {
+  let wg = WaitGroup::new().with_spin_rounds(0);
+  let ctr = Arc::new(AtomicUsize::new(0));
+
+  let (mut rest, last) = wg.add_tokens(4).split(3);
+  assert_eq!((rest.units(), last.units()), (3, 1));
+  assert_eq!(wg.remaining(), 4);
+
+  let mut handles = Vec::new();
+  for _ in 0..3 {
+    let (one, more) = rest.split(1);
+    rest = more;
+    let ctrx = ctr.clone();
+    handles.push(std::thread::spawn(move || {
+      ctrx.fetch_add(1, Ordering::Relaxed);
+      one.done();
+    }));
+  }
+  assert_eq!(rest.units(), 0);
+  drop(rest);
+  assert_eq!(last.done(), wg.remaining());
+
+  wg.wait();
+  assert_eq!(ctr.load(Ordering::Relaxed), 3);
+  for h in handles {
+    h.join().unwrap();
+  }
+}
+
+#[test]
+fn test_dropped_token_completes_its_units() {
+  let wg = WaitGroup::new();
+  let token = wg.add_tokens(2);
+  let h = std::thread::spawn(move || {
+    let _token = token;
+    panic!("worker failed before calling done");
+  });
+  wg.wait();
+  assert!(h.join().is_err());
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_token_split_out_of_range() {
+  let wg = WaitGroup::new();
+  let _ = wg.add_tokens(2).split(3);
+}
+
+fn visit(depth: u32, token: wg::Token, seen: Arc<AtomicUsize>) {
+  seen.fetch_add(1, Ordering::Relaxed);
+  if depth > 0 {
+    for _ in 0..3 {
+      let child = token.fork(1);
+      let seen = seen.clone();
+      std::thread::spawn(move || visit(depth - 1, child, seen));
+    }
+  }
+  std::thread::sleep(Duration::from_millis(1));
+  token.done();
+}
+
+#[test]
+fn test_forked_tokens_track_a_growing_tree() {
+  for spin_rounds in [0, 64] {
+    let wg = WaitGroup::new().with_spin_rounds(spin_rounds);
+    let seen = Arc::new(AtomicUsize::new(0));
+    let root = wg.add_token();
+    let s = seen.clone();
+    // The waiter is already blocked while the tree grows.
+    let h = std::thread::spawn(move || {
+      std::thread::sleep(Duration::from_millis(5));
+      visit(3, root, s);
+    });
+
+    wg.wait();
+    assert_eq!(seen.load(Ordering::Relaxed), 1 + 3 + 9 + 27);
+    assert_eq!(wg.remaining(), 0);
+    h.join().unwrap();
+  }
+}
+
+#[test]
+#[should_panic(expected = "holds no units")]
+fn test_fork_of_an_empty_token() {
+  let wg = WaitGroup::new();
+  let (_, empty) = wg.add_tokens(1).split(1);
+  let _ = empty.fork(1);
+}
+
+#[test]
+fn test_seal_rejects_new_work_but_drains_the_old() {
+  let wg = WaitGroup::new();
+  let t_wg = wg.add(2);
+  wg.seal();
+  assert!(wg.is_sealed());
+  assert_eq!(wg.try_add(1).unwrap_err(), wg::Sealed);
+  // The failed add left the counter alone.
+  assert_eq!(wg.remaining(), 2);
+
+  std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(5));
+    t_wg.done_n(2);
+  });
+  assert_eq!(wg.wait_outcome(), wg::WaitOutcome::Drained);
+}
+
+#[test]
+#[should_panic(expected = "add on a sealed WaitGroup")]
+fn test_add_on_a_sealed_group() {
+  let wg = WaitGroup::new();
+  wg.seal();
+  wg.add(1);
+}
+
+#[test]
+fn test_reset_releases_every_waiter() {
+  for spin_rounds in [0, 64] {
+    let wg = WaitGroup::new().with_spin_rounds(spin_rounds);
+    wg.add(3);
+    let waiters: Vec<_> = (0..4)
+      .map(|_| {
+        let wg = wg.clone();
+        std::thread::spawn(move || wg.wait_outcome())
+      })
+      .collect();
+
+    // Each waiter has read the reset count before it shows up here.
+    while wg.waiters() < 4 {
+      std::thread::yield_now();
+    }
+    wg.reset();
+    assert_eq!(wg.remaining(), 0);
+    for w in waiters {
+      assert_eq!(w.join().unwrap(), wg::WaitOutcome::Reset);
+    }
+    // A reset group is not sealed and can be reused.
+    wg.add(1).done();
+    assert_eq!(wg.wait_outcome(), wg::WaitOutcome::Drained);
+  }
+}
+
+#[test]
+fn test_waiters_counts_blocked_threads() {
+  for spin_rounds in [0, 64] {
+    let wg = WaitGroup::new().with_spin_rounds(spin_rounds);
+    let t_wg = wg.add(2);
+    assert_eq!(wg.waiters(), 0);
+    #[allow(unused_mut)]
+    let mut waiters = vec![
+      {
+        let wg = wg.clone();
+        std::thread::spawn(move || wg.wait())
+      },
+      {
+        let wg = wg.clone();
+        std::thread::spawn(move || wg.wait_for_at_most(1))
+      },
+    ];
+    #[cfg(any(feature = "portable-atomic", not(feature = "triomphe")))]
+    {
+      let weak = wg.downgrade();
+      waiters.push(std::thread::spawn(move || weak.wait()));
+    }
+
+    while wg.waiters() < waiters.len() {
+      std::thread::yield_now();
+    }
+    t_wg.done_n(2);
+    for w in waiters {
+      w.join().unwrap();
+    }
+    assert_eq!(wg.waiters(), 0);
+  }
+}